@@ -0,0 +1,38 @@
+use std::fmt;
+use clap::ValueEnum;
+
+use crate::client::GameClientDelegate;
+use crate::logic::OwnLogic;
+use crate::search::{Searcher, FishDifference, Mcts, RandomRollout, MctsBudget};
+
+/// A logic that can be pitted against others in the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum ArenaLogic {
+    /// The client's own logic.
+    Own,
+    /// An alpha-beta search maximizing the fish difference.
+    Alphabeta,
+    /// A Monte Carlo tree search using random rollouts.
+    Mcts,
+}
+
+impl ArenaLogic {
+    /// Creates a fresh delegate playing this logic.
+    pub fn create(self) -> Box<dyn GameClientDelegate> {
+        match self {
+            Self::Own => Box::new(OwnLogic),
+            Self::Alphabeta => Box::new(Searcher::new(FishDifference)),
+            Self::Mcts => Box::new(Mcts::new(RandomRollout::new(), MctsBudget::Iterations(usize::MAX))),
+        }
+    }
+}
+
+impl fmt::Display for ArenaLogic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Own => write!(f, "own"),
+            Self::Alphabeta => write!(f, "alphabeta"),
+            Self::Mcts => write!(f, "mcts"),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use crate::{game::Team, protocol::GameResult};
+
+/// The z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.959964;
+
+/// The distance to 0 and 1 that scores are clamped to when converting them to
+/// Elo differences, since perfect scores would yield infinite differences.
+const SCORE_EPSILON: f64 = 1e-3;
+
+/// Converts an expected score (between 0 and 1) to an Elo difference. Since a
+/// perfect score has no finite Elo difference, the score is clamped to
+/// `[SCORE_EPSILON, 1 - SCORE_EPSILON]`, i.e. the result is at most about ±1200.
+pub fn elo_from_score(score: f64) -> f64 {
+    let score = score.clamp(SCORE_EPSILON, 1.0 - SCORE_EPSILON);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Converts an Elo difference to an expected score (between 0 and 1).
+pub fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Aggregated results of a match from the perspective of one player.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MatchStatistics {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    fish_difference: i64,
+}
+
+impl MatchStatistics {
+    /// Creates new statistics.
+    #[inline]
+    pub fn new(wins: usize, draws: usize, losses: usize, fish_difference: i64) -> Self {
+        Self { wins, draws, losses, fish_difference }
+    }
+
+    /// Records a game that was won (Some(true)), lost (Some(false)) or
+    /// drawn (None) with the given fish difference.
+    pub fn record(&mut self, won: Option<bool>, fish_difference: i32) {
+        match won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+        self.fish_difference += fish_difference as i64;
+    }
+
+    /// Records a game from the perspective of the given team, taking the
+    /// fish from the second part of the scores (as reported by the server).
+    pub fn record_result(&mut self, result: &GameResult, team: Team) {
+        let fish = |t: Team| result.scores().iter()
+            .find(|(p, _)| p.team() == t)
+            .and_then(|(_, s)| s.parts().get(1).copied())
+            .unwrap_or_default();
+        let won = result.winner().as_ref().map(|p| p.team() == team);
+        self.record(won, fish(team) - fish(team.opponent()));
+    }
+
+    /// Adds the games recorded in the given statistics.
+    pub fn merge(&mut self, other: &MatchStatistics) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+        self.fish_difference += other.fish_difference;
+    }
+
+    /// The number of won games.
+    #[inline]
+    pub fn wins(&self) -> usize { self.wins }
+
+    /// The number of drawn games.
+    #[inline]
+    pub fn draws(&self) -> usize { self.draws }
+
+    /// The number of lost games.
+    #[inline]
+    pub fn losses(&self) -> usize { self.losses }
+
+    /// The total number of games.
+    #[inline]
+    pub fn games(&self) -> usize { self.wins + self.draws + self.losses }
+
+    /// The summed fish difference over all games.
+    #[inline]
+    pub fn fish_difference(&self) -> i64 { self.fish_difference }
+
+    /// The mean fish difference per game.
+    pub fn mean_fish_difference(&self) -> f64 {
+        self.fish_difference as f64 / self.games().max(1) as f64
+    }
+
+    /// The mean score per game, counting wins as 1 and draws as 0.5.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games().max(1) as f64
+    }
+
+    /// The variance of the score of a single game.
+    pub fn score_variance(&self) -> f64 {
+        let score = self.score();
+        let games = self.games().max(1) as f64;
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2)) / games
+    }
+
+    /// The estimated Elo difference.
+    pub fn elo(&self) -> f64 {
+        elo_from_score(self.score())
+    }
+
+    /// The 95% confidence interval of the Elo difference.
+    pub fn elo_interval(&self) -> (f64, f64) {
+        let score = self.score();
+        let error = Z_95 * (self.score_variance() / self.games().max(1) as f64).sqrt();
+        (elo_from_score((score - error).max(0.0)), elo_from_score((score + error).min(1.0)))
+    }
+}
+
+impl fmt::Display for MatchStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (lower, upper) = self.elo_interval();
+        write!(f, "W/D/L: {}/{}/{}, fish: {:+.2}/game, Elo: {:+.1} [{:+.1}, {:+.1}]",
+            self.wins, self.draws, self.losses, self.mean_fish_difference(), self.elo(), lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game::{Board, Field, State, Team, BOARD_FIELDS}, server::game_result};
+
+    use super::{MatchStatistics, elo_from_score, score_from_elo};
+
+    #[test]
+    fn test_elo() {
+        assert_eq!(elo_from_score(0.5), 0.0);
+        assert!((elo_from_score(score_from_elo(100.0)) - 100.0).abs() < 1e-9);
+
+        let statistics = MatchStatistics::new(60, 20, 20, 50);
+        assert_eq!(statistics.games(), 100);
+        assert_eq!(statistics.score(), 0.7);
+        assert!((statistics.elo() - 147.2).abs() < 0.1);
+        let (lower, upper) = statistics.elo_interval();
+        assert!(lower < statistics.elo() && statistics.elo() < upper);
+    }
+
+    #[test]
+    fn test_elo_of_perfect_scores() {
+        for statistics in [MatchStatistics::new(10, 0, 0, 0), MatchStatistics::new(0, 0, 10, 0)] {
+            let (lower, upper) = statistics.elo_interval();
+            assert!(statistics.elo().is_finite() && lower.is_finite() && upper.is_finite());
+            assert!(lower <= statistics.elo() && statistics.elo() <= upper);
+        }
+        assert!(MatchStatistics::new(10, 0, 0, 0).elo() > 1000.0);
+        assert!(MatchStatistics::new(0, 0, 10, 0).to_string().contains("Elo: -1199.8"));
+    }
+
+    #[test]
+    fn test_record_result() {
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [5, 2], None, Team::One);
+        let result = game_result(&state, None);
+        let mut statistics = MatchStatistics::default();
+        statistics.record_result(&result, Team::One);
+        assert_eq!(statistics, MatchStatistics::new(1, 0, 0, 3));
+        let mut other = MatchStatistics::default();
+        other.record_result(&result, Team::Two);
+        assert_eq!(other, MatchStatistics::new(0, 0, 1, -3));
+        statistics.merge(&other);
+        assert_eq!(statistics, MatchStatistics::new(1, 0, 1, 0));
+    }
+}
@@ -0,0 +1,9 @@
+mod arena_logic;
+mod match_statistics;
+mod runner;
+mod sprt;
+
+pub use arena_logic::*;
+pub use match_statistics::*;
+pub use runner::*;
+pub use sprt::*;
@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use rand::Rng;
+
+use crate::client::{GameClientDelegate, TimeBudget};
+use crate::game::{State, Team, Board, TEAMS};
+use crate::protocol::{GameResult, ScoreCause};
+use crate::server::game_result;
+
+use super::{MatchStatistics, Sprt, SprtDecision};
+
+/// Plays matches between two delegates in-process, without
+/// going through the XML protocol.
+#[derive(Debug, Clone)]
+pub struct Arena {
+    time_limit: Duration,
+    safety_margin: Duration,
+    sprt: Option<Sprt>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new(TimeBudget::SOFT_TIMEOUT)
+    }
+}
+
+impl Arena {
+    /// Creates a new arena granting the given time per move. By default,
+    /// a tenth of the time is reserved as the delegates' safety margin.
+    pub fn new(time_limit: Duration) -> Self {
+        Self { time_limit, safety_margin: time_limit / 10, sprt: None }
+    }
+
+    /// Sets the time subtracted from the time limit to obtain the delegates' deadline.
+    pub fn with_safety_margin(self, safety_margin: Duration) -> Self {
+        Self { safety_margin, ..self }
+    }
+
+    /// Stops the match early once the given sequential probability ratio test is decided.
+    pub fn with_sprt(self, sprt: Sprt) -> Self {
+        Self { sprt: Some(sprt), ..self }
+    }
+
+    /// Fetches the time per move.
+    pub fn time_limit(&self) -> Duration { self.time_limit }
+
+    /// Fetches the sequential probability ratio test.
+    pub fn sprt(&self) -> Option<Sprt> { self.sprt }
+
+    /// Plays up to the given number of games between fresh delegates created by the
+    /// given factories. Games are played in pairs on the same random board with
+    /// swapped teams. The statistics are from the perspective of the first delegate.
+    pub fn run<A, B>(
+        &self,
+        games: usize,
+        rng: &mut impl Rng,
+        mut new_a: impl FnMut() -> A,
+        mut new_b: impl FnMut() -> B,
+        mut on_game: impl FnMut(&MatchStatistics),
+    ) -> MatchStatistics
+    where A: GameClientDelegate,
+          B: GameClientDelegate {
+        let mut statistics = MatchStatistics::default();
+        let mut state = Self::initial_state(rng);
+
+        for game in 0..games {
+            if game > 0 && game % 2 == 0 {
+                state = Self::initial_state(rng);
+            }
+            let a_team = if game % 2 == 0 { Team::One } else { Team::Two };
+            let (mut a, mut b) = (new_a(), new_b());
+            let (final_state, result) = if a_team == Team::One {
+                self.play_game(state, &mut a, &mut b)
+            } else {
+                self.play_game(state, &mut b, &mut a)
+            };
+
+            let winner = result.winner().as_ref().map(|p| p.team());
+            let fish_difference = final_state.fish(a_team) as i32 - final_state.fish(a_team.opponent()) as i32;
+            statistics.record(winner.map(|w| w == a_team), fish_difference);
+            on_game(&statistics);
+
+            if let Some(sprt) = self.sprt {
+                let decision = sprt.decide(&statistics);
+                if decision != SprtDecision::Continue {
+                    info!("SPRT decided {:?} after {} games", decision, statistics.games());
+                    break;
+                }
+            }
+        }
+
+        statistics
+    }
+
+    /// Creates a starting state with a freshly generated board.
+    fn initial_state(rng: &mut impl Rng) -> State {
+        State::new(Board::generate(rng), 0, [0; TEAMS], None, Team::One)
+    }
+
+    /// Plays a single game from the given state, returning the final state and the result.
+    pub fn play_game(&self, mut state: State, one: &mut impl GameClientDelegate, two: &mut impl GameClientDelegate) -> (State, GameResult) {
+        one.on_welcome(Team::One);
+        two.on_welcome(Team::Two);
+        let mut violation = None;
+
+        loop {
+            one.on_update_state(&state);
+            two.on_update_state(&state);
+
+            if state.possible_moves().is_empty() {
+                break;
+            }
+
+            let team = state.current_team();
+            let delegate: &mut dyn GameClientDelegate = match team {
+                Team::One => one,
+                Team::Two => two,
+            };
+            let budget = TimeBudget::new(Instant::now(), self.time_limit, self.safety_margin);
+            let m = delegate.request_move(&state, team, budget);
+
+            if let Err(e) = state.validate(m) {
+                warn!("Team {} performed invalid move {}: {}", team, m, e);
+                violation = Some((team, ScoreCause::RuleViolation, format!("Invalid move {}: {}", m, e)));
+                break;
+            }
+            if budget.elapsed() > self.time_limit {
+                warn!("Team {} exceeded the time limit by {:?}", team, budget.elapsed() - self.time_limit);
+                violation = Some((team, ScoreCause::SoftTimeout, "Time limit exceeded".to_owned()));
+                break;
+            }
+            state.perform(m);
+        }
+
+        let result = game_result(&state, violation);
+        one.on_game_end(&result);
+        two.on_game_end(&result);
+        (state, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::search::{Mcts, RandomRollout, MctsBudget, Searcher, FishDifference};
+
+    use super::Arena;
+
+    #[test]
+    fn test_run() {
+        let arena = Arena::new(Duration::from_millis(50));
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut games = 0;
+        let statistics = arena.run(
+            4,
+            &mut rng,
+            || Mcts::new(RandomRollout::seeded(1), MctsBudget::Iterations(10)),
+            || Searcher::new(FishDifference),
+            |_| games += 1,
+        );
+        assert_eq!(statistics.games(), 4);
+        assert_eq!(games, 4);
+    }
+}
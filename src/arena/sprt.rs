@@ -0,0 +1,83 @@
+use super::{MatchStatistics, score_from_elo};
+
+/// The outcome of a sequential probability ratio test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtDecision {
+    /// The null hypothesis (Elo difference is `elo0`) was accepted.
+    AcceptH0,
+    /// The alternative hypothesis (Elo difference is `elo1`) was accepted.
+    AcceptH1,
+    /// More games are needed.
+    Continue,
+}
+
+/// A sequential probability ratio test for deciding whether the
+/// Elo difference is `elo1` rather than `elo0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Sprt {
+    /// Creates a new test with the given hypotheses and error probabilities.
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        Self { elo0, elo1, alpha, beta }
+    }
+
+    /// The lower and upper bound of the log-likelihood ratio.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// The approximate log-likelihood ratio of the given results. The score's
+    /// variance is estimated including a virtual game that is half won and half
+    /// lost, since one-sided results (e.g. only wins) would have no variance.
+    pub fn llr(&self, statistics: &MatchStatistics) -> f64 {
+        let (games, score) = (statistics.games() as f64, statistics.score());
+        let virtual_variance = 0.5 * (1.0 - score).powi(2) + 0.5 * score.powi(2);
+        let variance = (games * statistics.score_variance() + virtual_variance) / (games + 1.0);
+        let (s0, s1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        games * (s1 - s0) * (2.0 * score - s0 - s1) / (2.0 * variance)
+    }
+
+    /// Decides the test given the results so far.
+    pub fn decide(&self, statistics: &MatchStatistics) -> SprtDecision {
+        let llr = self.llr(statistics);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtDecision::AcceptH1
+        } else if llr <= lower {
+            SprtDecision::AcceptH0
+        } else {
+            SprtDecision::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::MatchStatistics;
+
+    use super::{Sprt, SprtDecision};
+
+    #[test]
+    fn test_decide() {
+        let sprt = Sprt::new(0.0, 50.0, 0.05, 0.05);
+        assert_eq!(sprt.decide(&MatchStatistics::new(5, 0, 5, 0)), SprtDecision::Continue);
+        assert_eq!(sprt.decide(&MatchStatistics::new(700, 0, 300, 0)), SprtDecision::AcceptH1);
+        assert_eq!(sprt.decide(&MatchStatistics::new(300, 0, 700, 0)), SprtDecision::AcceptH0);
+    }
+
+    #[test]
+    fn test_decide_one_sided() {
+        let sprt = Sprt::new(0.0, 50.0, 0.05, 0.05);
+        assert_eq!(sprt.decide(&MatchStatistics::new(0, 0, 0, 0)), SprtDecision::Continue);
+        assert_eq!(sprt.decide(&MatchStatistics::new(1, 0, 0, 0)), SprtDecision::Continue);
+        assert_eq!(sprt.decide(&MatchStatistics::new(10, 0, 0, 0)), SprtDecision::AcceptH1);
+        assert_eq!(sprt.decide(&MatchStatistics::new(0, 0, 10, 0)), SprtDecision::AcceptH0);
+        assert_eq!(sprt.decide(&MatchStatistics::new(0, 1, 0, 0)), SprtDecision::Continue);
+    }
+}
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+use std::time::Duration;
+use clap::Parser;
+use simplelog::{SimpleLogger, Config};
+use log::LevelFilter;
+use rand::{SeedableRng, rngs::StdRng};
+use socha_client_2023::arena::{Arena, ArenaLogic, Sprt};
+
+/// Pits two logics against each other in-process and reports match statistics.
+#[derive(Parser, Debug)]
+struct Args {
+    /// The first logic.
+    #[clap(value_enum, default_value = "own")]
+    one: ArenaLogic,
+    /// The second logic.
+    #[clap(value_enum, default_value = "own")]
+    two: ArenaLogic,
+    /// The maximum number of games to play.
+    #[clap(short, long, default_value_t = 100)]
+    games: usize,
+    /// The time per move in milliseconds.
+    #[clap(short, long, default_value_t = 100)]
+    time: u64,
+    /// The seed for generating boards.
+    #[clap(short, long)]
+    seed: Option<u64>,
+    /// Enables SPRT early stopping with the given null hypothesis Elo difference.
+    #[clap(long, requires = "elo1")]
+    elo0: Option<f64>,
+    /// The alternative hypothesis Elo difference for SPRT.
+    #[clap(long, requires = "elo0")]
+    elo1: Option<f64>,
+    /// The SPRT type I error probability.
+    #[clap(long, default_value_t = 0.05)]
+    alpha: f64,
+    /// The SPRT type II error probability.
+    #[clap(long, default_value_t = 0.05)]
+    beta: f64,
+    /// The level to log at.
+    #[clap(short, long, default_value = "Warn")]
+    level: String,
+}
+
+fn main() {
+    // Parse command line arguments
+    let args = Args::parse();
+    
+    // Set up logging
+    SimpleLogger::init(LevelFilter::from_str(&args.level).expect("Invalid log level."), Config::default()).expect("Could not initialize logger.");
+
+    let mut arena = Arena::new(Duration::from_millis(args.time));
+    if let (Some(elo0), Some(elo1)) = (args.elo0, args.elo1) {
+        arena = arena.with_sprt(Sprt::new(elo0, elo1, args.alpha, args.beta));
+    }
+    let mut rng = args.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+
+    let statistics = arena.run(args.games, &mut rng, || args.one.create(), || args.two.create(), |statistics| {
+        println!("Game {}: {}", statistics.games(), statistics);
+    });
+
+    println!("{} vs {} after {} games: {}", args.one, args.two, statistics.games(), statistics);
+    if let Some(sprt) = arena.sprt() {
+        let (lower, upper) = sprt.bounds();
+        println!("SPRT: LLR {:.2} [{:.2}, {:.2}] -> {:?}", sprt.llr(&statistics), lower, upper, sprt.decide(&statistics));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::Args;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use crossterm::{cursor, event, execute, terminal};
+use crossterm::event::{Event, KeyEventKind};
+use socha_client_2023::client::{DebugMode, SpectatorClient, SpectatorDelegate};
+use socha_client_2023::game::State;
+use socha_client_2023::protocol::GameResult;
+use socha_client_2023::replay::GameReplay;
+use socha_client_2023::viewer::GameViewer;
+
+/// How long to wait for key presses before checking for game updates.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shows replays and observed games in the terminal.
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Steps through a recorded replay (optionally gzip-compressed).
+    Replay {
+        /// The path of the replay.
+        path: PathBuf,
+    },
+    /// Follows a running game on the server as a spectator.
+    Observe {
+        /// The id of the room to observe.
+        room: String,
+        /// The game server's host address.
+        #[clap(short = 'H', long, default_value = "localhost")]
+        host: String,
+        /// The game server's port.
+        #[clap(short, long, default_value_t = 13050)]
+        port: u16,
+        /// The administrator passphrase, if the server requires one for observing.
+        #[clap(long)]
+        passphrase: Option<String>,
+    },
+}
+
+/// An update of an observed game.
+enum Update {
+    State(State),
+    Result(GameResult),
+    Error(String),
+}
+
+/// Forwards the progress of the observed game to the viewer.
+struct ForwardingDelegate(Sender<Update>);
+
+impl SpectatorDelegate for ForwardingDelegate {
+    fn on_update_state(&mut self, state: &State) {
+        let _ = self.0.send(Update::State(*state));
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        let _ = self.0.send(Update::Result(result.clone()));
+    }
+}
+
+/// Starts observing the given room in the background.
+fn observe(room: String, host: String, port: u16, passphrase: Option<String>) -> Receiver<Update> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let mut client = SpectatorClient::new(ForwardingDelegate(tx.clone()), debug_mode, room);
+        if let Some(passphrase) = passphrase {
+            client = client.with_passphrase(passphrase);
+        }
+        if let Err(e) = client.connect(&host, port) {
+            let _ = tx.send(Update::Error(format!("Observing failed: {:?}", e)));
+        }
+    });
+    rx
+}
+
+/// Runs the viewer until the user quits, applying the given updates as they arrive.
+fn run(out: &mut impl Write, viewer: &mut GameViewer, updates: Option<Receiver<Update>>) -> io::Result<()> {
+    let mut updates = updates;
+    let mut dirty = true;
+    loop {
+        while let Some(rx) = &updates {
+            match rx.try_recv() {
+                Ok(Update::State(state)) => viewer.push_state(state),
+                Ok(Update::Result(result)) => viewer.set_result(result),
+                Ok(Update::Error(message)) => viewer.set_status(&message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => updates = None,
+            }
+            dirty = true;
+        }
+
+        if dirty {
+            viewer.render(out)?;
+            out.flush()?;
+            dirty = false;
+        }
+
+        if event::poll(POLL_INTERVAL)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    if !viewer.handle_key(key) {
+                        return Ok(());
+                    }
+                    dirty = true;
+                }
+            } else {
+                dirty = true;
+            }
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let (mut viewer, updates) = match args.command {
+        Command::Replay { path } => match GameReplay::load(&path) {
+            Ok(replay) => (GameViewer::from_replay(&path.display().to_string(), &replay), None),
+            Err(e) => {
+                eprintln!("Could not load replay {}: {:?}", path.display(), e);
+                process::exit(1);
+            },
+        },
+        Command::Observe { room, host, port, passphrase } => {
+            let viewer = GameViewer::new(&format!("Room {}", room));
+            (viewer, Some(observe(room, host, port, passphrase)))
+        },
+    };
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().expect("Could not enable raw mode");
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide).expect("Could not set up terminal");
+
+    let result = run(&mut stdout, &mut viewer, updates);
+
+    let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+
+    if let Err(e) = result {
+        eprintln!("Viewer failed: {:?}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::Args;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+    }
+}
@@ -0,0 +1,168 @@
+use std::net::TcpStream;
+use std::io::{BufWriter, BufReader, Read, Write};
+use log::{info, warn, debug};
+use quick_xml::events::{Event as XmlEvent, BytesStart, BytesEnd};
+use quick_xml::{Reader, Writer};
+use crate::protocol::{Request, Event, SlotDescriptor};
+use crate::util::{Result, Element, Error};
+
+use super::PreparedRoom;
+
+/// A client that authenticates as an administrator to prepare and
+/// control games on the server, e.g. for scripting test matches.
+pub struct AdminClient<R, W> where R: Read, W: Write {
+    reader: Reader<BufReader<R>>,
+    writer: Writer<BufWriter<W>>,
+}
+
+impl AdminClient<TcpStream, TcpStream> {
+    /// Connects to the given address via TCP and
+    /// authenticates with the given passphrase.
+    pub fn connect(host: &str, port: u16, passphrase: &str) -> Result<Self> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address)?;
+        info!("Connected to {}", address);
+        Self::new(stream.try_clone()?, stream, passphrase)
+    }
+}
+
+impl<R, W> AdminClient<R, W> where R: Read, W: Write {
+    /// Performs the handshake over the given streams and
+    /// authenticates with the given passphrase.
+    pub fn new(read: R, write: W, passphrase: &str) -> Result<Self> {
+        let mut client = Self {
+            reader: Reader::from_reader(BufReader::new(read)),
+            writer: Writer::new(BufWriter::new(write)),
+        };
+
+        // Write <protocol> and authenticate
+        client.writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
+        info!("Authenticating");
+        client.send(Request::Authenticate { passphrase: passphrase.to_owned() })?;
+
+        // Read <protocol>
+        let mut buf = Vec::new();
+        loop {
+            match client.reader.read_event(&mut buf)? {
+                XmlEvent::Start(ref start) if start.name() == b"protocol" => {
+                    info!("Performed handshake");
+                    break
+                },
+                XmlEvent::Text(_) => (),
+                XmlEvent::Eof => return Err(Error::Eof),
+                e => warn!("Got unexpected event {:?}", e),
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Creates a room with the given slots and waits for the server to
+    /// respond with the reservation codes. If `pause` is set, the game
+    /// starts paused once all players joined.
+    pub fn prepare(&mut self, slots: Vec<SlotDescriptor>, pause: bool) -> Result<PreparedRoom> {
+        self.send(Request::Prepare { slots, pause })?;
+        loop {
+            let event_xml = Element::read_from(&mut self.reader)?;
+            debug!("Got event {}", event_xml);
+            match Event::try_from(&event_xml) {
+                Ok(Event::Prepared { room_id, reservations }) => {
+                    info!("Prepared room {} with reservations {:?}", room_id, reservations);
+                    return Ok(PreparedRoom::new(room_id, reservations));
+                },
+                Ok(event) => {
+                    debug!("Ignoring event {:?} while waiting for prepared room", event);
+                },
+                Err(Error::UnknownElement(element)) => {
+                    warn!("Got unknown tag <{}>: {}", element.name(), element);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Pauses or unpauses the game in the given room.
+    pub fn pause(&mut self, room_id: &str, pause: bool) -> Result<()> {
+        self.send(Request::Pause { room_id: room_id.to_owned(), pause })
+    }
+
+    /// Advances the paused game in the given room by a single move.
+    pub fn step(&mut self, room_id: &str) -> Result<()> {
+        self.send(Request::Step { room_id: room_id.to_owned() })
+    }
+
+    /// Closes the given room.
+    pub fn cancel(&mut self, room_id: &str) -> Result<()> {
+        self.send(Request::Cancel { room_id: room_id.to_owned() })
+    }
+
+    /// Ends the session by closing the protocol.
+    pub fn close(mut self) -> Result<()> {
+        self.writer.write_event(XmlEvent::End(BytesEnd::borrowed(b"protocol")))?;
+        self.writer.inner().flush()?;
+        Ok(())
+    }
+
+    /// Sends the given request.
+    fn send(&mut self, request: Request) -> Result<()> {
+        Element::from(request).write_to(&mut self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::{BufReader, Write}, thread};
+
+    use quick_xml::{Reader, events::Event as XmlEvent};
+
+    use crate::{protocol::{Event, Request, SlotDescriptor}, server::pipe, util::{Element, Error}};
+
+    use super::AdminClient;
+
+    #[test]
+    fn test_admin_session() {
+        let (mut to_client, from_server) = pipe();
+        let (to_server, from_client) = pipe();
+        let server = thread::spawn(move || {
+            let mut reader = Reader::from_reader(BufReader::new(from_client));
+            let mut buf = Vec::new();
+            while !matches!(reader.read_event(&mut buf).unwrap(), XmlEvent::Start(ref s) if s.name() == b"protocol") {}
+            write!(to_client, "<protocol>").unwrap();
+
+            let mut requests = Vec::new();
+            while let Ok(request_xml) = Element::read_from(&mut reader) {
+                let request = Request::try_from(&request_xml).unwrap();
+                if let Request::Prepare { slots, .. } = &request {
+                    let reservations = slots.iter().map(|s| format!("{}-code", s.display_name())).collect();
+                    write!(to_client, "{}", Element::from(Event::Joined { room_id: "other".to_owned() })).unwrap();
+                    write!(to_client, "{}", Element::from(Event::Prepared { room_id: "abc".to_owned(), reservations })).unwrap();
+                }
+                requests.push(request);
+            }
+            requests
+        });
+
+        let mut client = AdminClient::new(from_server, to_server, "secret").unwrap();
+        let room = client.prepare(vec![SlotDescriptor::new("one"), SlotDescriptor::new("two").with_reserved(false)], true).unwrap();
+        assert_eq!(room.room_id(), "abc");
+        assert_eq!(room.reservations(), &["one-code".to_owned(), "two-code".to_owned()]);
+        client.pause(room.room_id(), false).unwrap();
+        client.step(room.room_id()).unwrap();
+        client.cancel(room.room_id()).unwrap();
+        client.close().unwrap();
+        let requests = server.join().unwrap();
+
+        let names: Vec<_> = requests.into_iter().map(|r| Element::from(r).name().to_owned()).collect();
+        assert_eq!(names, ["authenticate", "prepare", "pause", "step", "cancel"]);
+    }
+
+    #[test]
+    fn test_server_error() {
+        let (mut to_client, from_server) = pipe();
+        let (to_server, _from_client) = pipe();
+        write!(to_client, r#"<protocol><errorpacket message="Wrong passphrase"/>"#).unwrap();
+        let mut client = AdminClient::new(from_server, to_server, "wrong").unwrap();
+        let result = client.prepare(vec![SlotDescriptor::new("one"), SlotDescriptor::new("two")], false);
+        assert!(matches!(result, Err(Error::ServerError(ref m)) if m == "Wrong passphrase"), "Expected server error, got {:?}", result);
+    }
+}
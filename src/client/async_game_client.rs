@@ -0,0 +1,271 @@
+use std::time::{Duration, Instant};
+use log::{info, warn, debug, error};
+use quick_xml::Writer;
+use tokio::io::{self, AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time;
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::{Request, Event, GameResult, EventPayload, RequestPayload};
+use crate::util::{AsyncElementReader, Result, Element, Error};
+
+use super::{AsyncGameClientDelegate, DebugMode, TimeBudget, check_move_request};
+
+/// The async counterpart of `GameClient`, which handles XML requests
+/// using tokio, manages the game state and awaits the delegate.
+pub struct AsyncGameClient<D> where D: AsyncGameClientDelegate {
+    delegate: D,
+    debug_mode: DebugMode,
+    reservation_code: Option<String>,
+    time_limit: Duration,
+    safety_margin: Duration,
+    fallback: bool,
+}
+
+impl<D> AsyncGameClient<D> where D: AsyncGameClientDelegate {
+    /// Creates a new client using the specified delegate.
+    pub fn new(delegate: D, debug_mode: DebugMode, reservation_code: Option<String>) -> Self {
+        Self {
+            delegate,
+            debug_mode,
+            reservation_code,
+            time_limit: TimeBudget::SOFT_TIMEOUT,
+            safety_margin: TimeBudget::DEFAULT_SAFETY_MARGIN,
+            fallback: false,
+        }
+    }
+
+    /// Sets the time the server grants per move (defaults to the soft timeout).
+    pub fn with_time_limit(self, time_limit: Duration) -> Self {
+        Self { time_limit, ..self }
+    }
+
+    /// Sets the time subtracted from the time limit to obtain the delegate's deadline.
+    pub fn with_safety_margin(self, safety_margin: Duration) -> Self {
+        Self { safety_margin, ..self }
+    }
+
+    /// Sets whether a legal fallback move should be sent if the delegate
+    /// overruns its deadline (disabled by default).
+    pub fn with_fallback(self, fallback: bool) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Connects to the given address via TCP and handles
+    /// the game messages until the game ends.
+    pub async fn connect(self, host: &str, port: u16) -> Result<GameResult> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address).await?;
+        info!("Connected to {}", address);
+
+        let mode = &self.debug_mode;
+        let game_result = if mode.debug_reader && !mode.debug_writer {
+            self.run(io::stdin(), stream).await?
+        } else if !mode.debug_reader && mode.debug_writer {
+            self.run(stream, io::stdout()).await?
+        } else if mode.debug_reader && mode.debug_writer {
+            self.run(io::stdin(), io::stdout()).await?
+        } else {
+            let (read, write) = stream.into_split();
+            self.run(read, write).await?
+        };
+
+        Ok(game_result)
+    }
+
+    /// Parses/handles game messages from the provided reader
+    /// until the game ends.
+    pub async fn run(mut self, read: impl AsyncRead + Unpin, mut write: impl AsyncWrite + Unpin) -> Result<GameResult> {
+        let mut reader = AsyncElementReader::new(read);
+
+        // Write <protocol>
+        write.write_all(b"<protocol>").await?;
+
+        // Send join request
+        let join_xml: Element = match &self.reservation_code {
+            Some(code) => Request::JoinPrepared { reservation_code: code.to_owned() },
+            None => Request::Join,
+        }.into();
+        info!("Sending join request {}", &join_xml);
+        Self::send(&join_xml, &mut write).await?;
+
+        // Read <protocol>
+        reader.read_start("protocol").await?;
+        info!("Performed handshake");
+
+        // Handle events from the server
+        let mut state: Option<State> = None;
+        let mut game_result: Option<GameResult> = None;
+        let mut history = GameHistory::new();
+        let mut my_team: Option<Team> = None;
+        loop {
+            let event_xml = reader.read_element().await?;
+            let received = Instant::now();
+
+            debug!("Got event {}", event_xml);
+            match Event::try_from(&event_xml) {
+                Ok(Event::Joined { room_id }) => {
+                    info!("Joined room {}", room_id);
+                },
+                Ok(Event::Left { room_id }) => {
+                    info!("Left room {}", room_id);
+                    break;
+                },
+                Ok(Event::Room { room_id, payload }) => {
+                    info!("Got {} in room {}", payload, room_id);
+                    match payload {
+                        EventPayload::Welcome(team) => {
+                            my_team = Some(team);
+                            self.delegate.on_welcome(team).await;
+                        },
+                        EventPayload::GameResult(result) => {
+                            self.delegate.on_game_end(&result).await;
+                            game_result = Some(result);
+                        },
+                        EventPayload::Memento(new_state) => {
+                            let recorded = history.states().len();
+                            history.push(new_state);
+                            self.delegate.on_update_state(&new_state).await;
+                            if history.states().len() > recorded {
+                                self.delegate.on_update_history(&history).await;
+                                if let Some((m, team)) = history.moves_with_teams().last() {
+                                    if my_team.is_some_and(|t| t != team) {
+                                        self.delegate.on_opponent_move(&m, &new_state).await;
+                                    }
+                                }
+                            }
+                            state = Some(new_state);
+                        },
+                        EventPayload::MoveRequest => {
+                            let (state, team) = check_move_request(state.as_ref(), my_team)?;
+                            let budget = TimeBudget::new(received, self.time_limit, self.safety_margin);
+                            let new_move = self.request_move(state, team, budget).await?;
+                            Self::send_move(new_move, &room_id, &mut write).await?;
+                        },
+                    };
+                },
+                Ok(event) => {
+                    warn!("Got unexpected event {:?}", event);
+                },
+                Err(Error::UnknownElement(element)) => {
+                    warn!("Got unknown tag <{}>: {}", element.name(), element);
+                },
+                Err(Error::ServerError(message)) => {
+                    error!("Server error: {}", message);
+                },
+                Err(e) => {
+                    warn!("Error while parsing event: {:?}", e);
+                },
+            }
+        }
+
+        if let Some(result) = game_result {
+            Ok(result)
+        } else {
+            Err(Error::InvalidState("Failed to receive game_result".to_string()))
+        }
+    }
+
+    /// Requests a move from the delegate. If the fallback is enabled and the
+    /// delegate overruns its deadline, its future is dropped and the first
+    /// possible move is returned instead.
+    async fn request_move(&mut self, state: &State, team: Team, budget: TimeBudget) -> Result<Move> {
+        let request = self.delegate.request_move(state, team, budget);
+        if !self.fallback {
+            return Ok(request.await);
+        }
+
+        match time::timeout(budget.remaining(), request).await {
+            Ok(new_move) => Ok(new_move),
+            Err(e) => {
+                let fallback = *state.possible_moves().first().ok_or_else(|| Error::InvalidState("No possible move at move request!".to_owned()))?;
+                warn!("Delegate did not respond in time ({}), sending fallback move {}", e, fallback);
+                Ok(fallback)
+            },
+        }
+    }
+
+    /// Sends the given move to the given room.
+    async fn send_move(new_move: Move, room_id: &str, write: &mut (impl AsyncWrite + Unpin)) -> Result<()> {
+        let request = Request::Room { room_id: room_id.to_owned(), payload: RequestPayload::Move(new_move) };
+        Self::send(&Element::from(request), write).await
+    }
+
+    /// Serializes the given element and writes it to the stream.
+    async fn send(element: &Element, write: &mut (impl AsyncWrite + Unpin)) -> Result<()> {
+        let mut writer = Writer::new(Vec::new());
+        element.write_to(&mut writer)?;
+        write.write_all(&writer.into_inner()).await?;
+        write.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, time::{Duration, Instant}};
+
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{client::{AsyncGameClientDelegate, BlockingDelegate, DebugMode, GameClientDelegate, TimeBudget}, game::{Board, Field, Move, State, Team, BOARD_FIELDS}, protocol::ScoreCause, server::LocalServer};
+
+    use super::AsyncGameClient;
+
+    struct AsyncRandomLogic(StdRng);
+
+    impl AsyncGameClientDelegate for AsyncRandomLogic {
+        async fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            tokio::task::yield_now().await;
+            *state.possible_moves().choose(&mut self.0).unwrap()
+        }
+    }
+
+    struct RandomLogic(StdRng);
+
+    impl GameClientDelegate for RandomLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            *state.possible_moves().choose(&mut self.0).unwrap()
+        }
+    }
+
+    struct SlowLogic;
+
+    impl AsyncGameClientDelegate for SlowLogic {
+        async fn request_move(&mut self, _state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            unreachable!()
+        }
+    }
+
+    fn debug_mode() -> DebugMode {
+        DebugMode { debug_reader: false, debug_writer: false }
+    }
+
+    #[tokio::test]
+    async fn test_play_against_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || LocalServer::random(&mut StdRng::seed_from_u64(8)).serve_tcp(&listener));
+
+        let one = AsyncGameClient::new(AsyncRandomLogic(StdRng::seed_from_u64(1)), debug_mode(), None);
+        let two = AsyncGameClient::new(BlockingDelegate::new(RandomLogic(StdRng::seed_from_u64(2))), debug_mode(), None);
+        // Clients can run on their own tasks
+        let one = tokio::spawn(one.connect("127.0.0.1", port));
+        let two_result = two.connect("127.0.0.1", port).await;
+        let one_result = one.await.unwrap();
+
+        let result = server.join().unwrap().unwrap();
+        assert_eq!(one_result.unwrap(), result);
+        assert_eq!(two_result.unwrap(), result);
+        assert!(result.scores().values().all(|s| s.cause() == ScoreCause::Regular));
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let budget = TimeBudget::new(Instant::now(), Duration::from_millis(50), Duration::from_millis(10));
+        let mut client = AsyncGameClient::new(SlowLogic, debug_mode(), None).with_fallback(true);
+        let m = client.request_move(&state, Team::One, budget).await.unwrap();
+        assert!(state.possible_moves().contains(&m));
+        assert!(budget.elapsed() < Duration::from_secs(1));
+    }
+}
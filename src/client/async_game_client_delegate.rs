@@ -0,0 +1,46 @@
+use std::future::Future;
+
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::GameResult;
+
+use super::TimeBudget;
+
+/// The async counterpart of `GameClientDelegate`, invoked by the
+/// `AsyncGameClient`. Since the hooks are awaited, the delegate may
+/// e.g. run work on other tasks or keep computing while waiting
+/// for the opponent's move. Synchronous delegates can be used by
+/// wrapping them in a `BlockingDelegate`.
+pub trait AsyncGameClientDelegate {
+    /// Invoked whenever the game state updates.
+    fn on_update_state(&mut self, _state: &State) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Invoked when the game ends.
+    fn on_game_end(&mut self, _result: &GameResult) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Invoked with the moves and states since the start of the
+    /// game whenever a new state is recorded.
+    fn on_update_history(&mut self, _history: &GameHistory) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Invoked when the opponent performed the given move,
+    /// resulting in the given state.
+    fn on_opponent_move(&mut self, _opponent_move: &Move, _state: &State) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Invoked when the welcome message is received
+    /// with the player's team.
+    fn on_welcome(&mut self, _team: Team) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Requests a move from the delegate. The returned future should
+    /// resolve before the budget's deadline. If the client's fallback is
+    /// enabled, the future is dropped once the deadline is overrun.
+    fn request_move(&mut self, state: &State, my_team: Team, budget: TimeBudget) -> impl Future<Output = Move> + Send;
+}
@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::task;
+
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::GameResult;
+
+use super::{AsyncGameClientDelegate, GameClientDelegate, TimeBudget};
+
+/// Adapts a synchronous delegate to the async client by running its
+/// moves on tokio's blocking thread pool, so the runtime keeps processing
+/// other tasks (and the client can send a fallback move) in the meantime.
+/// An overrunning computation finishes in the background, the next hook
+/// then waits for it like with the synchronous client.
+pub struct BlockingDelegate<D> where D: GameClientDelegate + Send + 'static {
+    delegate: Arc<Mutex<D>>,
+    /// A copy of the client's history, updated with only the new states.
+    history: Arc<Mutex<GameHistory>>,
+}
+
+impl<D> BlockingDelegate<D> where D: GameClientDelegate + Send + 'static {
+    /// Wraps the given delegate.
+    pub fn new(delegate: D) -> Self {
+        Self { delegate: Arc::new(Mutex::new(delegate)), history: Arc::default() }
+    }
+
+    /// Runs the given function with the delegate on the blocking thread pool.
+    async fn with_delegate<T>(&self, f: impl FnOnce(&mut D) -> T + Send + 'static) -> T where T: Send + 'static {
+        let delegate = self.delegate.clone();
+        task::spawn_blocking(move || f(&mut delegate.lock().expect("Delegate panicked")))
+            .await
+            .expect("Delegate panicked")
+    }
+}
+
+impl<D> AsyncGameClientDelegate for BlockingDelegate<D> where D: GameClientDelegate + Send + 'static {
+    async fn on_update_state(&mut self, state: &State) {
+        let state = *state;
+        self.with_delegate(move |d| d.on_update_state(&state)).await
+    }
+
+    async fn on_game_end(&mut self, result: &GameResult) {
+        let result = result.clone();
+        self.with_delegate(move |d| d.on_game_end(&result)).await
+    }
+
+    async fn on_update_history(&mut self, history: &GameHistory) {
+        // Usually the copy is exactly one state behind, otherwise
+        // (e.g. if the client started a new game) it is replaced.
+        let copy = self.history.clone();
+        let in_sync = {
+            let copy = copy.lock().expect("Delegate panicked");
+            copy.initial_state() == history.initial_state() && copy.states().len() + 1 == history.states().len()
+        };
+        let replacement = if in_sync { None } else { Some(history.clone()) };
+        let new_state = history.current_state().copied();
+        self.with_delegate(move |d| {
+            let mut copy = copy.lock().expect("Delegate panicked");
+            match (replacement, new_state) {
+                (Some(replacement), _) => *copy = replacement,
+                (None, Some(state)) => { copy.push(state); },
+                (None, None) => {},
+            }
+            d.on_update_history(&copy)
+        }).await
+    }
+
+    async fn on_opponent_move(&mut self, opponent_move: &Move, state: &State) {
+        let (opponent_move, state) = (*opponent_move, *state);
+        self.with_delegate(move |d| d.on_opponent_move(&opponent_move, &state)).await
+    }
+
+    async fn on_welcome(&mut self, team: Team) {
+        self.with_delegate(move |d| d.on_welcome(team)).await
+    }
+
+    async fn request_move(&mut self, state: &State, my_team: Team, budget: TimeBudget) -> Move {
+        let state = *state;
+        self.with_delegate(move |d| d.request_move(&state, my_team, budget)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{client::{AsyncGameClientDelegate, GameClientDelegate, TimeBudget}, game::{Board, GameHistory, Move, State, Team}};
+
+    use super::BlockingDelegate;
+
+    #[derive(Default)]
+    struct HistoryLogic(GameHistory);
+
+    impl GameClientDelegate for HistoryLogic {
+        fn on_update_history(&mut self, history: &GameHistory) {
+            self.0 = history.clone();
+        }
+
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            state.possible_moves()[0]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_history() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut delegate = BlockingDelegate::new(HistoryLogic::default());
+        for _ in 0..2 {
+            let mut state = State::new(Board::generate(&mut rng), 0, [0, 0], None, Team::One);
+            let mut history = GameHistory::new();
+            history.push(state);
+            delegate.on_update_history(&history).await;
+            while let Some(&m) = state.possible_moves().choose(&mut rng) {
+                state.perform(m);
+                history.push(state);
+                delegate.on_update_history(&history).await;
+                assert_eq!(delegate.delegate.lock().unwrap().0, history);
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+/// A configuration that determines whether
+/// the reader and/or the writer of a stream
+/// should be swapped by stdio to ease debugging.
+pub struct DebugMode {
+    pub debug_reader: bool,
+    pub debug_writer: bool,
+}
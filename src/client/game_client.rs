@@ -0,0 +1,882 @@
+use std::net::TcpStream;
+use std::io::{self, BufWriter, BufReader, Read, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, warn, debug, error};
+use quick_xml::events::{Event as XmlEvent, BytesStart};
+use quick_xml::{Reader, Writer, Error as XmlError};
+use crate::arena::MatchStatistics;
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::{Request, Event, GameResult, EventPayload, RequestPayload};
+use crate::replay::ReplayRecorder;
+use crate::util::{Result, Element, Error};
+
+use super::{GameClientDelegate, DebugMode, ReconnectPolicy, TimeBudget};
+
+/// The client which handles XML requests, manages
+/// the game state and invokes the delegate.
+pub struct GameClient<D> where D: GameClientDelegate {
+    delegate: D,
+    debug_mode: DebugMode,
+    reservation_code: Option<String>,
+    time_limit: Duration,
+    safety_margin: Duration,
+    fallback: Option<FallbackRequest<D>>,
+    pondering: bool,
+    reconnect_policy: ReconnectPolicy,
+    replay_directory: Option<PathBuf>,
+    compress_replays: bool,
+    replay_moves: bool,
+}
+
+/// What the client knows about its game, which
+/// is retained when reconnecting.
+#[derive(Default)]
+struct Session {
+    room_id: Option<String>,
+    state: Option<State>,
+    history: GameHistory,
+    my_team: Option<Team>,
+    recorder: Option<ReplayRecorder>,
+}
+
+impl Session {
+    /// Records to the replay, if one is recorded. Since the replay is
+    /// not essential to the game, recording stops on errors.
+    fn record(&mut self, f: impl FnOnce(&mut ReplayRecorder) -> Result<()>) {
+        if let Some(Err(e)) = self.recorder.as_mut().map(f) {
+            warn!("Stopping replay recording after error: {:?}", e);
+            self.recorder = None;
+        }
+    }
+
+    /// Finishes the replay, if one is recorded.
+    fn finish_replay(&mut self) {
+        if let Some(Err(e)) = self.recorder.take().map(ReplayRecorder::finish) {
+            warn!("Could not finish replay: {:?}", e);
+        }
+    }
+}
+
+/// Requests a move from the delegate, sending a fallback move if
+/// it overruns (see `GameClient::with_fallback`). Returns the sent move.
+type FallbackRequest<D> = fn(&mut D, &State, Team, TimeBudget, &mut dyn FnMut(Move) -> Result<()>) -> Result<Move>;
+
+/// Where the client reads the server's events from.
+enum EventSource<R> where R: Read {
+    /// Reads the events on the client's thread.
+    Blocking(Reader<BufReader<R>>),
+    /// Reads the events on a separate thread, so the delegate can ponder while waiting for them.
+    Background(Receiver<Result<Element>>),
+}
+
+impl<R> EventSource<R> where R: Read + Send + 'static {
+    /// Spawns a thread reading events until the room is left.
+    fn background(mut reader: Reader<BufReader<R>>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || loop {
+            let event_xml = Element::read_from(&mut reader);
+            let done = event_xml.as_ref().map_or(true, |e| e.name() == "left");
+            if tx.send(event_xml).is_err() || done {
+                break;
+            }
+        });
+        Self::Background(rx)
+    }
+}
+
+/// The state the delegate ponders on during the opponent's turn.
+#[derive(Debug, Clone, Copy)]
+struct Pondering {
+    state: State,
+    team: Team,
+}
+
+impl<D> GameClient<D> where D: GameClientDelegate {
+    /// Creates a new client using the specified delegate.
+    pub fn new(delegate: D, debug_mode: DebugMode, reservation_code: Option<String>) -> Self {
+        Self {
+            delegate,
+            debug_mode,
+            reservation_code,
+            time_limit: TimeBudget::SOFT_TIMEOUT,
+            safety_margin: TimeBudget::DEFAULT_SAFETY_MARGIN,
+            fallback: None,
+            pondering: false,
+            reconnect_policy: ReconnectPolicy::none(),
+            replay_directory: None,
+            compress_replays: false,
+            replay_moves: true,
+        }
+    }
+
+    /// Sets the time the server grants per move (defaults to the soft timeout).
+    pub fn with_time_limit(self, time_limit: Duration) -> Self {
+        Self { time_limit, ..self }
+    }
+
+    /// Sets the time subtracted from the time limit to obtain the delegate's deadline.
+    pub fn with_safety_margin(self, safety_margin: Duration) -> Self {
+        Self { safety_margin, ..self }
+    }
+
+    /// Sets whether the delegate should ponder during the opponent's turn
+    /// (disabled by default). See `GameClientDelegate::ponder`. Since the
+    /// messages are then read on a separate thread, pondering requires
+    /// connecting or using `run_pondering` rather than `run`.
+    pub fn with_pondering(self, pondering: bool) -> Self {
+        Self { pondering, ..self }
+    }
+
+    /// Sets whether and how often the client reconnects if the connection
+    /// drops (never by default). After reconnecting, the client rejoins
+    /// using its reservation code or the previously joined room's id.
+    pub fn with_reconnect_policy(self, reconnect_policy: ReconnectPolicy) -> Self {
+        Self { reconnect_policy, ..self }
+    }
+
+    /// Sets a directory to record a replay of each game to, containing the
+    /// received mementos, the sent moves and the result (none by default).
+    pub fn with_replay_directory(self, replay_directory: PathBuf) -> Self {
+        Self { replay_directory: Some(replay_directory), ..self }
+    }
+
+    /// Sets whether replays should be gzip-compressed (disabled by default).
+    pub fn with_replay_compression(self, compress_replays: bool) -> Self {
+        Self { compress_replays, ..self }
+    }
+
+    /// Sets whether replays should contain the sent moves (enabled by default).
+    /// Such replays are not in the official format (see `ReplayRecorder::record_move`),
+    /// disabling this records replays just like the official server's.
+    pub fn with_replay_moves(self, replay_moves: bool) -> Self {
+        Self { replay_moves, ..self }
+    }
+    
+    /// Blocks the thread and begins reading XML messages
+    /// from the provided address via TCP. If the connection
+    /// drops, the client reconnects according to its reconnect
+    /// policy and resumes the game.
+    pub fn connect(mut self, host: &str, port: u16) -> Result<GameResult> {
+        self.play(host, port).map(|(game_result, _)| game_result)
+    }
+
+    /// Blocks the thread and plays the given number of games one after
+    /// another with the same delegate, joining again after each result.
+    /// Since reservation codes are only valid for a single game, playing
+    /// several games with one fails. Returns the aggregated results.
+    pub fn connect_games(mut self, host: &str, port: u16, games: usize) -> Result<MatchStatistics> {
+        if self.reservation_code.is_some() && games > 1 {
+            return Err(Error::InvalidState(format!("Cannot play {} games with a single reservation code", games)));
+        }
+        let mut statistics = MatchStatistics::default();
+        for game in 0..games {
+            match self.play(host, port)? {
+                (result, Some(team)) => statistics.record_result(&result, team),
+                (_, None) => warn!("Not counting game {}, since no team was welcomed", game + 1),
+            }
+            info!("Game {}/{}: {}", game + 1, games, statistics);
+        }
+        Ok(statistics)
+    }
+
+    /// Blocks the thread and plays the given total number of games in
+    /// the given number of concurrent sessions, each of which plays its
+    /// games sequentially using a client created by the given factory
+    /// (invoked with the session's index). Sessions that fail are logged
+    /// and not counted. Returns the aggregated results.
+    pub fn connect_concurrently(host: &str, port: u16, sessions: usize, games: usize, new_client: impl Fn(usize) -> Self + Sync) -> MatchStatistics {
+        if sessions == 0 {
+            return MatchStatistics::default();
+        }
+        let new_client = &new_client;
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..sessions)
+                .map(|session| {
+                    let session_games = games / sessions + usize::from(session < games % sessions);
+                    scope.spawn(move || new_client(session).connect_games(host, port, session_games))
+                })
+                .collect();
+            let mut statistics = MatchStatistics::default();
+            for (session, handle) in handles.into_iter().enumerate() {
+                match handle.join().expect("Session panicked") {
+                    Ok(session_statistics) => statistics.merge(&session_statistics),
+                    Err(e) => error!("Session {} failed: {:?}", session, e),
+                }
+            }
+            statistics
+        })
+    }
+
+    /// Plays a single game, reconnecting according to the reconnect
+    /// policy. Returns the result along with the welcomed team.
+    fn play(&mut self, host: &str, port: u16) -> Result<(GameResult, Option<Team>)> {
+        let mut session = Session::default();
+        let result = self.play_session(host, port, &mut session);
+        session.finish_replay();
+        result.map(|game_result| (game_result, session.my_team))
+    }
+
+    /// Plays the session's game, reconnecting according to the reconnect policy.
+    fn play_session(&mut self, host: &str, port: u16, session: &mut Session) -> Result<GameResult> {
+        let mut attempt = 0;
+        loop {
+            let previous_turn = session.state.map(|s| s.turn());
+            let error = match self.connect_session(host, port, session) {
+                Ok(game_result) => return Ok(game_result),
+                Err(e @ (Error::Io(_) | Error::Xml(XmlError::Io(_)) | Error::Eof)) => e,
+                Err(e) => return Err(e),
+            };
+            // Only count consecutive attempts without progress
+            if session.state.map(|s| s.turn()) != previous_turn {
+                attempt = 0;
+            }
+            match self.reconnect_policy.backoff(attempt) {
+                Some(backoff) => {
+                    warn!("Connection lost ({:?}), reconnecting in {:?} (attempt {}/{})", error, backoff, attempt + 1, self.reconnect_policy.max_retries());
+                    thread::sleep(backoff);
+                    attempt += 1;
+                },
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Connects to the given address via TCP and handles
+    /// the game messages until the game ends or the connection drops.
+    fn connect_session(&mut self, host: &str, port: u16, session: &mut Session) -> Result<GameResult> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address)?;
+        info!("Connected to {}", address);
+        
+        // Begin parsing game messages from the stream.
+        // List all combinations of modes explicitly,
+        // since they generate different generic instantiations
+        // of `run_game`.
+
+        let mode = &self.debug_mode;
+        if mode.debug_reader && !mode.debug_writer {
+            self.run_session(io::stdin(), stream, self.event_source(), session)
+        } else if !mode.debug_reader && mode.debug_writer {
+            self.run_session(stream, io::stdout(), self.event_source(), session)
+        } else if mode.debug_reader && mode.debug_writer {
+            self.run_session(io::stdin(), io::stdout(), self.event_source(), session)
+        } else {
+            self.run_session(stream.try_clone()?, stream, self.event_source(), session)
+        }
+    }
+    
+    /// Blocks the thread and parses/handles game messages
+    /// from the provided reader. The delegate does not ponder,
+    /// use `run_pondering` for that.
+    pub fn run(mut self, read: impl Read, write: impl Write) -> Result<GameResult> {
+        if self.pondering {
+            warn!("Not pondering, since the client is run without a reader thread");
+        }
+        let mut session = Session::default();
+        let result = self.run_session(read, write, EventSource::Blocking, &mut session);
+        session.finish_replay();
+        result
+    }
+
+    /// Blocks the thread and parses/handles game messages from the
+    /// provided reader. If pondering is enabled, the messages are read
+    /// on a separate thread, so the delegate can ponder while waiting.
+    pub fn run_pondering(mut self, read: impl Read + Send + 'static, write: impl Write) -> Result<GameResult> {
+        let event_source = self.event_source();
+        let mut session = Session::default();
+        let result = self.run_session(read, write, event_source, &mut session);
+        session.finish_replay();
+        result
+    }
+
+    /// Chooses to read events on a separate thread if pondering is enabled.
+    fn event_source<R>(&self) -> fn(Reader<BufReader<R>>) -> EventSource<R> where R: Read + Send + 'static {
+        if self.pondering { EventSource::background } else { EventSource::Blocking }
+    }
+
+    /// Handles game messages from the provided reader, rejoining
+    /// the session's room if it already joined one. After the
+    /// handshake, the events are read from the given source.
+    fn run_session<R>(&mut self, read: R, write: impl Write, event_source: fn(Reader<BufReader<R>>) -> EventSource<R>, session: &mut Session) -> Result<GameResult> where R: Read {
+        let mut buf = Vec::new();
+        let mut reader = Reader::from_reader(BufReader::new(read));
+        let mut writer = Writer::new(BufWriter::new(write));
+
+        // Write <protocol>
+        writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
+        
+        // Send join request
+        let join_xml: Element = match (&self.reservation_code, &session.room_id) {
+            (Some(code), _) => Request::JoinPrepared { reservation_code: code.to_owned() },
+            (None, Some(room_id)) => Request::JoinRoom { room_id: room_id.to_owned() },
+            (None, None) => Request::Join,
+        }.into();
+        info!("Sending join request {}", &join_xml);
+        join_xml.write_to(&mut writer)?;
+
+        // Read <protocol>
+        loop {
+            match reader.read_event(&mut buf)? {
+                XmlEvent::Start(ref start) if start.name() == b"protocol" => {
+                    info!("Performed handshake");
+                    break
+                },
+                XmlEvent::Text(_) => (),
+                XmlEvent::Eof => return Err(Error::Eof),
+                e => warn!("Got unexpected event {:?}", e),
+            }
+        }
+
+        // Pondering requires reading events on a separate thread
+        let mut events = event_source(reader);
+        let can_ponder = self.pondering && matches!(events, EventSource::Background(_));
+
+        // Handle events from the server
+        let mut game_result: Option<GameResult> = None;
+        let mut pondering: Option<Pondering> = None;
+        loop {
+            let event_xml = match self.next_event(&mut events, &mut pondering) {
+                Ok(event_xml) => event_xml,
+                // The game is over anyway if the connection drops after the result
+                Err(_) if game_result.is_some() => break,
+                Err(e) => {
+                    self.end_pondering(&mut pondering, None);
+                    return Err(e);
+                },
+            };
+            let received = Instant::now();
+
+            debug!("Got event {}", event_xml);
+            match Event::try_from(&event_xml) {
+                Ok(Event::Joined { room_id }) => {
+                    info!("Joined room {}", room_id);
+                    if let (None, Some(directory)) = (&session.recorder, &self.replay_directory) {
+                        match ReplayRecorder::create(directory, &room_id, self.compress_replays) {
+                            Ok(recorder) => session.recorder = Some(recorder),
+                            Err(e) => warn!("Could not create replay: {:?}", e),
+                        }
+                    }
+                    session.room_id = Some(room_id);
+                },
+                Ok(Event::Left { room_id }) => {
+                    info!("Left room {}", room_id);
+                    self.end_pondering(&mut pondering, None);
+                    break;
+                },
+                Ok(Event::Room { room_id, payload }) => {
+                    info!("Got {} in room {}", payload, room_id);
+                    match payload {
+                        EventPayload::Welcome(team) => {
+                            session.my_team = Some(team);
+                            self.delegate.on_welcome(team);
+                        },
+                        EventPayload::GameResult(result) => {
+                            self.end_pondering(&mut pondering, None);
+                            self.delegate.on_game_end(&result);
+                            session.record(|r| r.record_result(&result));
+                            game_result = Some(result);
+                        },
+                        EventPayload::Memento(new_state) => {
+                            // The first memento after the own move only confirms it
+                            if let Some(p) = pondering.filter(|p| new_state.turn() > p.state.turn()) {
+                                let opponent_move = new_state.last_move().filter(|_| new_state.turn() == p.state.turn() + 1);
+                                self.end_pondering(&mut pondering, opponent_move);
+                            }
+                            let history = &mut session.history;
+                            let recorded = history.states().len();
+                            history.push(new_state);
+                            self.delegate.on_update_state(&new_state);
+                            if history.states().len() > recorded {
+                                self.delegate.on_update_history(history);
+                                if let Some((m, team)) = history.moves_with_teams().last() {
+                                    if session.my_team.is_some_and(|t| t != team) {
+                                        self.delegate.on_opponent_move(&m, &new_state);
+                                    }
+                                }
+                            }
+                            session.record(|r| r.record_memento(&new_state));
+                            session.state = Some(new_state);
+                        },
+                        EventPayload::MoveRequest => {
+                            let (state, team) = check_move_request(session.state.as_ref(), session.my_team)?;
+                            let budget = TimeBudget::new(received, self.time_limit, self.safety_margin);
+                            let new_move = self.request_move(state, team, budget, &room_id, &mut writer)?;
+                            if can_ponder && state.validate(new_move).is_ok() {
+                                let next_state = state.child(new_move);
+                                if !next_state.is_over() && next_state.current_team() != team {
+                                    pondering = Some(Pondering { state: next_state, team });
+                                }
+                            }
+                            if self.replay_moves {
+                                session.record(|r| r.record_move(new_move));
+                            }
+                        },
+                    };
+                },
+                Ok(event) => {
+                    warn!("Got unexpected event {:?}", event);
+                },
+                Err(Error::UnknownElement(element)) => {
+                    warn!("Got unknown tag <{}>: {}", element.name(), element);
+                },
+                Err(Error::ServerError(message)) => {
+                    error!("Server error: {}", message);
+                },
+                Err(e) => {
+                    warn!("Error while parsing event: {:?}", e);
+                },
+            }
+        }
+
+        if let Some(result) = game_result {
+            Ok(result)
+        }else {
+            Err(Error::InvalidState("Failed to receive game_result".to_string()))
+        }
+    }
+
+    /// Waits for the next event, letting the delegate
+    /// ponder in the meantime if applicable.
+    fn next_event<R>(&mut self, events: &mut EventSource<R>, pondering: &mut Option<Pondering>) -> Result<Element> where R: Read {
+        let rx = match events {
+            EventSource::Blocking(reader) => return Element::read_from(reader),
+            EventSource::Background(rx) => rx,
+        };
+        if let Some(p) = *pondering {
+            loop {
+                match rx.try_recv() {
+                    Ok(event_xml) => return event_xml,
+                    Err(TryRecvError::Empty) => if !self.delegate.ponder(&p.state, p.team) {
+                        break;
+                    },
+                    Err(TryRecvError::Disconnected) => return Err(Error::Eof),
+                }
+            }
+        }
+        rx.recv().unwrap_or(Err(Error::Eof))
+    }
+
+    /// Notifies the delegate that pondering ended, if it was pondering.
+    fn end_pondering(&mut self, pondering: &mut Option<Pondering>, opponent_move: Option<Move>) {
+        if pondering.take().is_some() {
+            debug!("Ending pondering (opponent move: {:?})", opponent_move);
+            self.delegate.on_ponder_end(opponent_move);
+        }
+    }
+
+    /// Requests a move from the delegate and sends it, returning the sent move.
+    fn request_move(&mut self, state: &State, team: Team, budget: TimeBudget, room_id: &str, writer: &mut Writer<impl Write>) -> Result<Move> {
+        let mut send = |new_move| Self::send_move(new_move, room_id, writer);
+        match self.fallback {
+            Some(request_move) => request_move(&mut self.delegate, state, team, budget, &mut send),
+            None => {
+                let new_move = self.delegate.request_move(state, team, budget);
+                send(new_move).map(|_| new_move)
+            },
+        }
+    }
+
+    /// Sends the given move to the given room.
+    fn send_move(new_move: Move, room_id: &str, writer: &mut Writer<impl Write>) -> Result<()> {
+        let request = Request::Room { room_id: room_id.to_owned(), payload: RequestPayload::Move(new_move) };
+        let request_xml = Element::from(request);
+        request_xml.write_to(writer)
+    }
+}
+
+impl<D> GameClient<D> where D: GameClientDelegate + Send {
+    /// Sets whether a legal fallback move should be sent if the delegate
+    /// overruns its deadline (disabled by default). The delegate then runs
+    /// on a separate thread, hence it has to be `Send`.
+    /// 
+    /// Note that the client still waits for the delegate to return before
+    /// handling further messages, since the delegate cannot be used by two
+    /// threads at once. The fallback thus guards against delegates that
+    /// overrun their deadline, but not against delegates that never return.
+    pub fn with_fallback(self, fallback: bool) -> Self {
+        let fallback = if fallback { Some(Self::request_move_with_fallback as FallbackRequest<D>) } else { None };
+        Self { fallback, ..self }
+    }
+
+    /// Requests a move from the delegate on a separate thread and sends it. If the
+    /// delegate overruns its deadline, the first possible move is sent instead and
+    /// the delegate's move is discarded once it returns.
+    fn request_move_with_fallback(delegate: &mut D, state: &State, team: Team, budget: TimeBudget, send: &mut dyn FnMut(Move) -> Result<()>) -> Result<Move> {
+        thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+            scope.spawn(move || {
+                let _ = tx.send(delegate.request_move(state, team, budget));
+            });
+            match rx.recv_timeout(budget.remaining()) {
+                Ok(new_move) => send(new_move).map(|_| new_move),
+                Err(e) => {
+                    let fallback = *state.possible_moves().first().ok_or_else(|| Error::InvalidState("No possible move at move request!".to_owned()))?;
+                    warn!("Delegate did not respond in time ({:?}), sending fallback move {}", e, fallback);
+                    send(fallback).map(|_| fallback)
+                    // The scope waits for the delegate to finish
+                },
+            }
+        })
+    }
+}
+
+/// Fetches the state and the welcomed team at a move request, checking
+/// that the state agrees that it is the welcomed team's turn.
+pub(crate) fn check_move_request(state: Option<&State>, my_team: Option<Team>) -> Result<(&State, Team)> {
+    let state = state.ok_or_else(|| Error::InvalidState("No state available at move request!".to_owned()))?;
+    let team = my_team.ok_or_else(|| Error::InvalidState("No team welcomed at move request!".to_owned()))?;
+    if state.current_team() != team {
+        return Err(Error::InvalidState(format!("Move requested for team {}, but the state (turn {}) says it is team {}'s turn!", team, state.turn(), state.current_team())));
+    }
+    Ok((state, team))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, env, fs, io::{BufReader, Write}, net::{TcpListener, TcpStream}, rc::Rc, str::FromStr, sync::{Arc, Mutex}, thread, time::Duration};
+
+    use quick_xml::{Reader, events::Event as XmlEvent};
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{client::{DebugMode, GameClientDelegate, ReconnectPolicy, TimeBudget}, game::{Board, Field, GameHistory, Move, State, Team, BOARD_FIELDS}, protocol::{Event, EventPayload, Request, RequestPayload}, replay::GameReplay, server::{game_result, pipe, LocalServer}, util::{Element, Error}};
+
+    use super::GameClient;
+
+    #[derive(Default)]
+    struct Recording {
+        states: Vec<State>,
+        ponder_calls: usize,
+        pondered: Option<State>,
+        ponder_ends: Vec<(State, Option<Move>)>,
+    }
+
+    /// Picks the first possible move and records the pondering.
+    struct PonderingLogic(Arc<Mutex<Recording>>);
+
+    impl GameClientDelegate for PonderingLogic {
+        fn on_update_state(&mut self, state: &State) {
+            self.0.lock().unwrap().states.push(*state);
+        }
+
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            state.possible_moves()[0]
+        }
+
+        fn ponder(&mut self, state: &State, _my_team: Team) -> bool {
+            let mut recording = self.0.lock().unwrap();
+            recording.ponder_calls += 1;
+            recording.pondered = Some(*state);
+            thread::sleep(Duration::from_millis(1));
+            true
+        }
+
+        fn on_ponder_end(&mut self, opponent_move: Option<Move>) {
+            let mut recording = self.0.lock().unwrap();
+            let pondered = recording.pondered.take().unwrap();
+            recording.ponder_ends.push((pondered, opponent_move));
+        }
+    }
+
+    /// Picks random moves slowly, giving the opponent time to ponder.
+    struct SlowLogic(StdRng);
+
+    impl GameClientDelegate for SlowLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            thread::sleep(Duration::from_millis(5));
+            *state.possible_moves().choose(&mut self.0).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_pondering() {
+        let (to_one, from_server_one) = pipe();
+        let (to_server_one, from_one) = pipe();
+        let (to_two, from_server_two) = pipe();
+        let (to_server_two, from_two) = pipe();
+        let server = thread::spawn(move || LocalServer::random(&mut StdRng::seed_from_u64(9)).run((from_one, to_one), (from_two, to_two)));
+        let debug_mode = || DebugMode { debug_reader: false, debug_writer: false };
+        let two = thread::spawn(move || GameClient::new(SlowLogic(StdRng::seed_from_u64(1)), debug_mode(), None).run(from_server_two, to_server_two));
+
+        let recording = Arc::new(Mutex::new(Recording::default()));
+        GameClient::new(PonderingLogic(recording.clone()), debug_mode(), None)
+            .with_pondering(true)
+            .run_pondering(from_server_one, to_server_one)
+            .unwrap();
+        server.join().unwrap().unwrap();
+        two.join().unwrap().unwrap();
+
+        let recording = recording.lock().unwrap();
+        assert!(recording.ponder_calls > 0);
+        assert!(recording.ponder_ends.iter().any(|(_, m)| m.is_some()));
+        for &(pondered, opponent_move) in &recording.ponder_ends {
+            assert_eq!(pondered.current_team(), Team::Two);
+            if let Some(m) = opponent_move {
+                assert!(recording.states.contains(&pondered.child(m)));
+            }
+        }
+    }
+
+    /// The opponent's moves with the resulting states and the most recent history.
+    type Observations = (Vec<(Move, State)>, GameHistory);
+
+    /// Picks random moves and records the opponent's moves and the history.
+    struct HistoryLogic(StdRng, Arc<Mutex<Observations>>);
+
+    impl GameClientDelegate for HistoryLogic {
+        fn on_update_history(&mut self, history: &GameHistory) {
+            self.1.lock().unwrap().1 = history.clone();
+        }
+
+        fn on_opponent_move(&mut self, opponent_move: &Move, state: &State) {
+            self.1.lock().unwrap().0.push((*opponent_move, *state));
+        }
+
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            *state.possible_moves().choose(&mut self.0).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let one = Arc::new(Mutex::new(Default::default()));
+        let two = Arc::new(Mutex::new(Default::default()));
+        LocalServer::random(&mut StdRng::seed_from_u64(10))
+            .play(HistoryLogic(StdRng::seed_from_u64(1), one.clone()), HistoryLogic(StdRng::seed_from_u64(2), two.clone()))
+            .unwrap();
+
+        let (one_opponent_moves, one_history) = &*one.lock().unwrap();
+        let (two_opponent_moves, two_history) = &*two.lock().unwrap();
+        assert_eq!(one_history, two_history);
+        let final_state = *one_history.current_state().unwrap();
+        assert!(final_state.is_over());
+        assert_eq!(one_history.moves().iter().fold(*one_history.initial_state().unwrap(), |s, &m| s.child(m)), final_state);
+
+        for (team, opponent_moves) in [(Team::One, one_opponent_moves), (Team::Two, two_opponent_moves)] {
+            let moves: Vec<_> = opponent_moves.iter().map(|&(m, _)| m).collect();
+            assert_eq!(moves, one_history.moves_of(team.opponent()).collect::<Vec<_>>());
+            assert!(opponent_moves.iter().all(|(m, s)| s.last_move() == Some(*m)));
+        }
+    }
+
+    #[test]
+    fn test_move_request_for_other_team() {
+        let (mut to_client, from_server) = pipe();
+        let (to_server, _from_client) = pipe();
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        write!(to_client, "<protocol>").unwrap();
+        for payload in [EventPayload::Welcome(Team::Two), EventPayload::Memento(state), EventPayload::MoveRequest] {
+            write!(to_client, "{}", Element::from(Event::Room { room_id: "test".to_owned(), payload })).unwrap();
+        }
+
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let result = GameClient::new(SlowLogic(StdRng::seed_from_u64(3)), debug_mode, None).run(from_server, to_server);
+        assert!(matches!(result, Err(Error::InvalidState(_))), "Expected invalid state, got {:?}", result);
+    }
+
+    #[test]
+    fn test_replay_after_error() {
+        let (mut to_client, from_server) = pipe();
+        let (to_server, _from_client) = pipe();
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        write!(to_client, "<protocol>{}", Element::from(Event::Joined { room_id: "r".to_owned() })).unwrap();
+        for payload in [EventPayload::Welcome(Team::Two), EventPayload::Memento(state), EventPayload::MoveRequest] {
+            write!(to_client, "{}", Element::from(Event::Room { room_id: "r".to_owned(), payload })).unwrap();
+        }
+
+        let directory = env::temp_dir().join(format!("socha-client-replay-error-test-{}", std::process::id()));
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let result = GameClient::new(SlowLogic(StdRng::seed_from_u64(3)), debug_mode, None)
+            .with_replay_directory(directory.clone())
+            .with_replay_compression(true)
+            .run(from_server, to_server);
+        assert!(result.is_err());
+
+        let path = fs::read_dir(&directory).unwrap().next().unwrap().unwrap().path();
+        let replay = GameReplay::load(&path).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(replay.states(), &[state]);
+    }
+
+    /// Picks the last possible move, but only after the deadline.
+    struct StuckLogic;
+
+    impl GameClientDelegate for StuckLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, budget: TimeBudget) -> Move {
+            thread::sleep(budget.remaining() + Duration::from_millis(200));
+            *state.possible_moves().last().unwrap()
+        }
+    }
+
+    /// Picks the first possible move and counts the requests in a
+    /// shared counter, which makes the delegate not `Send`.
+    struct SharedLogic(Rc<Cell<usize>>);
+
+    impl GameClientDelegate for SharedLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            self.0.set(self.0.get() + 1);
+            state.possible_moves()[0]
+        }
+    }
+
+    /// Runs the given client in a game where it is asked for a single
+    /// move in the given state, returning the move it sent.
+    fn request_single_move<D>(client: GameClient<D>, state: State) -> Move where D: GameClientDelegate {
+        let (mut to_client, from_server) = pipe();
+        let (to_server, from_client) = pipe();
+        let room = |payload| Element::from(Event::Room { room_id: "r".to_owned(), payload });
+        let result = game_result(&state, None);
+        write!(to_client, "<protocol>{}{}{}", room(EventPayload::Welcome(Team::One)), room(EventPayload::Memento(state)), room(EventPayload::MoveRequest)).unwrap();
+        write!(to_client, "{}{}</protocol>", room(EventPayload::GameResult(result.clone())), Element::from(Event::Left { room_id: "r".to_owned() })).unwrap();
+        assert_eq!(client.run(from_server, to_server).unwrap(), result);
+
+        let mut reader = Reader::from_reader(BufReader::new(from_client));
+        let mut buf = Vec::new();
+        while !matches!(reader.read_event(&mut buf).unwrap(), XmlEvent::Start(ref s) if s.name() == b"protocol") {}
+        assert!(matches!(Request::try_from(&Element::read_from(&mut reader).unwrap()).unwrap(), Request::Join));
+        match Request::try_from(&Element::read_from(&mut reader).unwrap()).unwrap() {
+            Request::Room { payload: RequestPayload::Move(m), .. } => m,
+            request => panic!("Expected move, got {:?}", request),
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let client = GameClient::new(StuckLogic, debug_mode, None)
+            .with_time_limit(Duration::from_millis(50))
+            .with_safety_margin(Duration::ZERO)
+            .with_fallback(true);
+        assert_eq!(request_single_move(client, state), state.possible_moves()[0]);
+    }
+
+    #[test]
+    fn test_non_send_delegate() {
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let requests = Rc::new(Cell::new(0));
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let client = GameClient::new(SharedLogic(requests.clone()), debug_mode, None);
+        assert_eq!(request_single_move(client, state), state.possible_moves()[0]);
+        assert_eq!(requests.get(), 1);
+    }
+
+    /// Accepts a client, performing the handshake and
+    /// returning its join request along with the streams.
+    fn accept(listener: &TcpListener) -> (Request, Reader<BufReader<TcpStream>>, TcpStream) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = Reader::from_reader(BufReader::new(stream.try_clone().unwrap()));
+        let mut buf = Vec::new();
+        while !matches!(reader.read_event(&mut buf).unwrap(), XmlEvent::Start(ref s) if s.name() == b"protocol") {}
+        let join = Request::try_from(&Element::read_from(&mut reader).unwrap()).unwrap();
+        (join, reader, stream)
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let server = thread::spawn(move || {
+            let room = |payload| Element::from(Event::Room { room_id: "r".to_owned(), payload });
+
+            // The first connection drops after the initial state
+            let (join, _, mut stream) = accept(&listener);
+            assert!(matches!(join, Request::Join));
+            write!(stream, "<protocol>{}{}{}", Element::from(Event::Joined { room_id: "r".to_owned() }), room(EventPayload::Welcome(Team::One)), room(EventPayload::Memento(state))).unwrap();
+            drop(stream);
+
+            // The client should rejoin and still know the state
+            let (join, mut reader, mut stream) = accept(&listener);
+            assert!(matches!(join, Request::JoinRoom { ref room_id } if room_id == "r"), "Expected rejoin, got {:?}", join);
+            write!(stream, "<protocol>{}", room(EventPayload::MoveRequest)).unwrap();
+            let m = match Request::try_from(&Element::read_from(&mut reader).unwrap()).unwrap() {
+                Request::Room { payload: RequestPayload::Move(m), .. } => m,
+                request => panic!("Expected move, got {:?}", request),
+            };
+            let result = game_result(&state.child(m), None);
+            write!(stream, "{}{}</protocol>", room(EventPayload::GameResult(result.clone())), Element::from(Event::Left { room_id: "r".to_owned() })).unwrap();
+            result
+        });
+
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let policy = ReconnectPolicy::new(3).with_initial_backoff(Duration::from_millis(10));
+        let result = GameClient::new(SlowLogic(StdRng::seed_from_u64(4)), debug_mode, None)
+            .with_reconnect_policy(policy)
+            .connect("127.0.0.1", port)
+            .unwrap();
+        assert_eq!(result, server.join().unwrap());
+    }
+
+    #[test]
+    fn test_connect_games_with_reservation() {
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let result = GameClient::new(SlowLogic(StdRng::seed_from_u64(5)), debug_mode, Some("code".to_owned())).connect_games("127.0.0.1", 0, 2);
+        assert!(matches!(result, Err(Error::InvalidState(_))), "Expected invalid state, got {:?}", result.map(|s| s.games()));
+    }
+
+    #[test]
+    fn test_connect_concurrently() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let mut rng = StdRng::seed_from_u64(12);
+            (0..3).map(|_| LocalServer::random(&mut rng).serve_tcp(&listener).unwrap()).collect::<Vec<_>>()
+        });
+
+        let debug_mode = || DebugMode { debug_reader: false, debug_writer: false };
+        let statistics = GameClient::connect_concurrently("127.0.0.1", port, 2, 6, |session| {
+            GameClient::new(HistoryLogic(StdRng::seed_from_u64(session as u64), Default::default()), debug_mode(), None)
+        });
+        let results = server.join().unwrap();
+
+        // Both sessions play in each game, so their results cancel out
+        assert_eq!(statistics.games(), 6);
+        assert_eq!(statistics.wins(), statistics.losses());
+        assert_eq!(statistics.draws(), 2 * results.iter().filter(|r| r.winner().is_none()).count());
+        assert_eq!(statistics.fish_difference(), 0);
+    }
+
+    #[test]
+    fn test_replay() {
+        let (to_one, from_server_one) = pipe();
+        let (to_server_one, from_one) = pipe();
+        let (to_two, from_server_two) = pipe();
+        let (to_server_two, from_two) = pipe();
+        let server = thread::spawn(move || LocalServer::random(&mut StdRng::seed_from_u64(13)).run((from_one, to_one), (from_two, to_two)));
+        let debug_mode = || DebugMode { debug_reader: false, debug_writer: false };
+        let two = thread::spawn(move || GameClient::new(SlowLogic(StdRng::seed_from_u64(1)), debug_mode(), None).run(from_server_two, to_server_two));
+
+        let directory = env::temp_dir().join(format!("socha-client-replay-test-{}", std::process::id()));
+        let observations = Arc::new(Mutex::new(Default::default()));
+        let result = GameClient::new(HistoryLogic(StdRng::seed_from_u64(2), observations.clone()), debug_mode(), None)
+            .with_replay_directory(directory.clone())
+            .run(from_server_one, to_server_one)
+            .unwrap();
+        server.join().unwrap().unwrap();
+        two.join().unwrap().unwrap();
+
+        let paths: Vec<_> = fs::read_dir(&directory).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(paths.len(), 1);
+        let replay = Element::from_str(&fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let history = &observations.lock().unwrap().1;
+        let mut states = Vec::new();
+        let mut moves = Vec::new();
+        let mut results = Vec::new();
+        for message in replay.childs_by_name("room") {
+            match (Event::try_from(message), Request::try_from(message)) {
+                (Ok(Event::Room { payload: EventPayload::Memento(state), .. }), _) => states.push(state),
+                (Ok(Event::Room { payload: EventPayload::GameResult(result), .. }), _) => results.push(result),
+                (_, Ok(Request::Room { payload: RequestPayload::Move(m), .. })) => moves.push(m),
+                _ => panic!("Unexpected message {}", message),
+            }
+        }
+        assert_eq!(states, history.states());
+        assert_eq!(moves, history.moves_of(Team::One).collect::<Vec<_>>());
+        assert_eq!(results, vec![result]);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::GameResult;
+
+use super::TimeBudget;
+
+/// A handler that implements the game player's
+/// behavior, usually employing some custom move
+/// selection strategy.
+pub trait GameClientDelegate {
+    /// Invoked whenever the game state updates.
+    fn on_update_state(&mut self, _state: &State) {}
+    
+    /// Invoked when the game ends.
+    fn on_game_end(&mut self, _result: &GameResult) {}
+
+    /// Invoked with the moves and states since the start of the
+    /// game whenever a new state is recorded.
+    fn on_update_history(&mut self, _history: &GameHistory) {}
+
+    /// Invoked when the opponent performed the given move,
+    /// resulting in the given state.
+    fn on_opponent_move(&mut self, _opponent_move: &Move, _state: &State) {}
+    
+    /// Invoked when the welcome message is received
+    /// with the player's team.
+    fn on_welcome(&mut self, _team: Team) {}
+    
+    /// Requests a move from the delegate. This method
+    /// should implement the "main" game logic and return
+    /// before the budget's deadline.
+    fn request_move(&mut self, state: &State, my_team: Team, budget: TimeBudget) -> Move;
+
+    /// Invoked repeatedly during the opponent's turn if the client has pondering
+    /// enabled, with the state resulting from the delegate's own move. Each call
+    /// should only perform a small increment of work, since the client checks for
+    /// the opponent's move in between. Returns whether the delegate wants to
+    /// keep pondering.
+    fn ponder(&mut self, _state: &State, _my_team: Team) -> bool { false }
+
+    /// Invoked once pondering ends, i.e. when the state after the opponent's
+    /// move arrives, with the opponent's actual move (or None if it is unknown,
+    /// e.g. because the game ended).
+    fn on_ponder_end(&mut self, _opponent_move: Option<Move>) {}
+}
+
+impl<D> GameClientDelegate for Box<D> where D: GameClientDelegate + ?Sized {
+    fn on_update_state(&mut self, state: &State) {
+        (**self).on_update_state(state)
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        (**self).on_game_end(result)
+    }
+
+    fn on_update_history(&mut self, history: &GameHistory) {
+        (**self).on_update_history(history)
+    }
+
+    fn on_opponent_move(&mut self, opponent_move: &Move, state: &State) {
+        (**self).on_opponent_move(opponent_move, state)
+    }
+
+    fn on_welcome(&mut self, team: Team) {
+        (**self).on_welcome(team)
+    }
+
+    fn request_move(&mut self, state: &State, my_team: Team, budget: TimeBudget) -> Move {
+        (**self).request_move(state, my_team, budget)
+    }
+
+    fn ponder(&mut self, state: &State, my_team: Team) -> bool {
+        (**self).ponder(state, my_team)
+    }
+
+    fn on_ponder_end(&mut self, opponent_move: Option<Move>) {
+        (**self).on_ponder_end(opponent_move)
+    }
+}
@@ -0,0 +1,31 @@
+mod admin_client;
+#[cfg(feature = "async")]
+mod async_game_client;
+#[cfg(feature = "async")]
+mod async_game_client_delegate;
+#[cfg(feature = "async")]
+mod blocking_delegate;
+mod debug_mode;
+mod game_client;
+mod game_client_delegate;
+mod prepared_room;
+mod reconnect_policy;
+mod spectator_client;
+mod spectator_delegate;
+mod time_budget;
+
+pub use admin_client::*;
+#[cfg(feature = "async")]
+pub use async_game_client::*;
+#[cfg(feature = "async")]
+pub use async_game_client_delegate::*;
+#[cfg(feature = "async")]
+pub use blocking_delegate::*;
+pub use debug_mode::*;
+pub use game_client::*;
+pub use game_client_delegate::*;
+pub use prepared_room::*;
+pub use reconnect_policy::*;
+pub use spectator_client::*;
+pub use spectator_delegate::*;
+pub use time_budget::*;
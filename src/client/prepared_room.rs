@@ -0,0 +1,20 @@
+/// A room created by an administrator, along with the
+/// reservation codes the players can join it with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedRoom {
+    room_id: String,
+    reservations: Vec<String>,
+}
+
+impl PreparedRoom {
+    /// Creates a new prepared room.
+    pub fn new(room_id: String, reservations: Vec<String>) -> Self {
+        Self { room_id, reservations }
+    }
+
+    /// Fetches the room's id.
+    pub fn room_id(&self) -> &str { &self.room_id }
+
+    /// Fetches the reservation codes in the order of the slots.
+    pub fn reservations(&self) -> &[String] { &self.reservations }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+/// Determines whether and how often a client reconnects after
+/// its connection dropped, waiting with exponential backoff
+/// between the attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReconnectPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// The default backoff before the first reconnection attempt.
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+    /// The default upper bound for the backoff.
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+    /// Creates a policy that never reconnects.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Creates a policy that reconnects up to the given
+    /// number of times in a row, using the default backoff.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Sets the backoff before the first attempt, which doubles with each further attempt.
+    pub fn with_initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self { initial_backoff, ..self }
+    }
+
+    /// Sets the upper bound for the backoff.
+    pub fn with_max_backoff(self, max_backoff: Duration) -> Self {
+        Self { max_backoff, ..self }
+    }
+
+    /// The maximum number of consecutive reconnection attempts.
+    pub fn max_retries(self) -> u32 { self.max_retries }
+
+    /// The time to wait before the given (zero-based) attempt,
+    /// or `None` if no further attempt should be made.
+    pub fn backoff(self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+        Some(self.initial_backoff.saturating_mul(factor).min(self.max_backoff))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ReconnectPolicy;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(ReconnectPolicy::none().backoff(0), None);
+
+        let policy = ReconnectPolicy::new(40)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1));
+        assert_eq!(policy.backoff(0), Some(Duration::from_millis(100)));
+        assert_eq!(policy.backoff(1), Some(Duration::from_millis(200)));
+        assert_eq!(policy.backoff(3), Some(Duration::from_millis(800)));
+        assert_eq!(policy.backoff(4), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff(39), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff(40), None);
+    }
+}
@@ -0,0 +1,245 @@
+use std::net::TcpStream;
+use std::io::{self, BufWriter, BufReader, Read, Write};
+use log::{info, warn, debug, error};
+use quick_xml::events::{Event as XmlEvent, BytesStart};
+use quick_xml::{Reader, Writer};
+use crate::game::GameHistory;
+use crate::protocol::{Request, Event, GameResult, EventPayload};
+use crate::util::{Result, Element, Error};
+
+use super::{SpectatorDelegate, DebugMode};
+
+/// A client that observes a room as a spectator and feeds the
+/// game's progress to the delegate. Observing requires
+/// administrator rights on the official server, so a
+/// passphrase usually has to be configured.
+pub struct SpectatorClient<D> where D: SpectatorDelegate {
+    delegate: D,
+    debug_mode: DebugMode,
+    room_id: String,
+    passphrase: Option<String>,
+}
+
+impl<D> SpectatorClient<D> where D: SpectatorDelegate {
+    /// Creates a new client observing the given room using the specified delegate.
+    pub fn new(delegate: D, debug_mode: DebugMode, room_id: String) -> Self {
+        Self {
+            delegate,
+            debug_mode,
+            room_id,
+            passphrase: None,
+        }
+    }
+
+    /// Sets the administrator passphrase to authenticate with before observing.
+    pub fn with_passphrase(self, passphrase: String) -> Self {
+        Self { passphrase: Some(passphrase), ..self }
+    }
+
+    /// Blocks the thread and begins reading XML messages
+    /// from the provided address via TCP.
+    pub fn connect(self, host: &str, port: u16) -> Result<GameResult> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address)?;
+        info!("Connected to {}", address);
+
+        let mode = &self.debug_mode;
+        let game_result = if mode.debug_reader && !mode.debug_writer {
+            self.run(io::stdin(), stream)?
+        } else if !mode.debug_reader && mode.debug_writer {
+            self.run(stream, io::stdout())?
+        } else if mode.debug_reader && mode.debug_writer {
+            self.run(io::stdin(), io::stdout())?
+        } else {
+            self.run(stream.try_clone()?, stream)?
+        };
+
+        Ok(game_result)
+    }
+
+    /// Blocks the thread and parses/handles game messages from
+    /// the provided reader until the observed game ends.
+    pub fn run(mut self, read: impl Read, write: impl Write) -> Result<GameResult> {
+        let mut buf = Vec::new();
+        let mut reader = Reader::from_reader(BufReader::new(read));
+        let mut writer = Writer::new(BufWriter::new(write));
+
+        // Write <protocol>
+        writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
+
+        // Authenticate and send observation request
+        if let Some(passphrase) = &self.passphrase {
+            info!("Authenticating");
+            Element::from(Request::Authenticate { passphrase: passphrase.to_owned() }).write_to(&mut writer)?;
+        }
+        let observe_xml: Element = Request::Observe { room_id: self.room_id.clone() }.into();
+        info!("Sending observation request {}", &observe_xml);
+        observe_xml.write_to(&mut writer)?;
+
+        // Read <protocol>
+        loop {
+            match reader.read_event(&mut buf)? {
+                XmlEvent::Start(ref start) if start.name() == b"protocol" => {
+                    info!("Performed handshake");
+                    break
+                },
+                XmlEvent::Text(_) => (),
+                XmlEvent::Eof => return Err(Error::Eof),
+                e => warn!("Got unexpected event {:?}", e),
+            }
+        }
+
+        // Handle events from the server
+        let mut history = GameHistory::new();
+        let mut game_result: Option<GameResult> = None;
+        while game_result.is_none() {
+            let event_xml = Element::read_from(&mut reader)?;
+
+            debug!("Got event {}", event_xml);
+            match Event::try_from(&event_xml) {
+                Ok(Event::Observed { room_id }) => {
+                    info!("Observing room {}", room_id);
+                    self.delegate.on_observe(&room_id);
+                },
+                Ok(Event::Left { room_id }) => {
+                    info!("Left room {}", room_id);
+                    break;
+                },
+                Ok(Event::Room { room_id, .. }) if room_id != self.room_id => {
+                    debug!("Ignoring message in unobserved room {}", room_id);
+                },
+                Ok(Event::Room { room_id, payload }) => {
+                    info!("Got {} in room {}", payload, room_id);
+                    match payload {
+                        EventPayload::Memento(new_state) => {
+                            let recorded = history.states().len();
+                            history.push(new_state);
+                            self.delegate.on_update_state(&new_state);
+                            if history.states().len() > recorded {
+                                self.delegate.on_update_history(&history);
+                                if let Some((m, team)) = history.moves_with_teams().last() {
+                                    self.delegate.on_move(team, &m, &new_state);
+                                }
+                            }
+                        },
+                        EventPayload::GameResult(result) => {
+                            self.delegate.on_game_end(&result);
+                            game_result = Some(result);
+                        },
+                        EventPayload::Welcome(_) | EventPayload::MoveRequest => {
+                            warn!("Ignoring {} while observing", payload);
+                        },
+                    }
+                },
+                Ok(event) => {
+                    warn!("Got unexpected event {:?}", event);
+                },
+                Err(Error::UnknownElement(element)) => {
+                    warn!("Got unknown tag <{}>: {}", element.name(), element);
+                },
+                Err(Error::ServerError(message)) => {
+                    error!("Server error: {}", message);
+                },
+                Err(e) => {
+                    warn!("Error while parsing event: {:?}", e);
+                },
+            }
+        }
+
+        game_result.ok_or_else(|| Error::InvalidState("Failed to receive game_result".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::{Read, Write}, sync::{Arc, Mutex}};
+
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{client::{DebugMode, SpectatorDelegate}, game::{GameHistory, Move, State, Team}, protocol::{Event, EventPayload, GameResult}, server::{game_result, pipe, LocalServer}, util::Element};
+
+    use super::SpectatorClient;
+
+    /// What the spectator was notified about.
+    #[derive(Default)]
+    struct Observations {
+        observed: Vec<String>,
+        states: Vec<State>,
+        moves: Vec<(Team, Move, State)>,
+        history: GameHistory,
+        result: Option<GameResult>,
+    }
+
+    struct RecordingSpectator(Arc<Mutex<Observations>>);
+
+    impl SpectatorDelegate for RecordingSpectator {
+        fn on_observe(&mut self, room_id: &str) {
+            self.0.lock().unwrap().observed.push(room_id.to_owned());
+        }
+
+        fn on_update_state(&mut self, state: &State) {
+            self.0.lock().unwrap().states.push(*state);
+        }
+
+        fn on_update_history(&mut self, history: &GameHistory) {
+            self.0.lock().unwrap().history = history.clone();
+        }
+
+        fn on_move(&mut self, team: Team, performed_move: &Move, state: &State) {
+            self.0.lock().unwrap().moves.push((team, *performed_move, *state));
+        }
+
+        fn on_game_end(&mut self, result: &GameResult) {
+            self.0.lock().unwrap().result = Some(result.clone());
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut state = *LocalServer::random(&mut rng).state();
+        let mut states = vec![state];
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            state.perform(m);
+            states.push(state);
+        }
+        let result = game_result(&state, None);
+
+        let (mut to_client, from_server) = pipe();
+        let (to_server, mut from_client) = pipe();
+        let room = |room_id: &str, payload| Element::from(Event::Room { room_id: room_id.to_owned(), payload });
+        write!(to_client, "<protocol>{}", Element::from(Event::Observed { room_id: "test".to_owned() })).unwrap();
+        for (i, &s) in states.iter().enumerate() {
+            write!(to_client, "{}", room("test", EventPayload::Memento(s))).unwrap();
+            // Move requests and other rooms should be ignored
+            write!(to_client, "{}", room("other", EventPayload::Memento(states[0]))).unwrap();
+            if i == 0 {
+                write!(to_client, "{}", room("test", EventPayload::MoveRequest)).unwrap();
+            }
+        }
+        write!(to_client, "{}", room("test", EventPayload::GameResult(result.clone()))).unwrap();
+
+        let observations = Arc::new(Mutex::new(Observations::default()));
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let client_result = SpectatorClient::new(RecordingSpectator(observations.clone()), debug_mode, "test".to_owned())
+            .with_passphrase("secret".to_owned())
+            .run(from_server, to_server)
+            .unwrap();
+        assert_eq!(client_result, result);
+
+        let mut requests = String::new();
+        from_client.read_to_string(&mut requests).unwrap();
+        assert_eq!(requests, r#"<protocol><authenticate passphrase="secret"/><observe roomId="test"/>"#);
+
+        let observations = observations.lock().unwrap();
+        assert_eq!(observations.observed, vec!["test".to_owned()]);
+        assert_eq!(observations.states, states);
+        assert_eq!(observations.history.states(), &states[..]);
+        assert_eq!(observations.moves.len(), states.len() - 1);
+        for (&(team, m, s), previous) in observations.moves.iter().zip(&states) {
+            assert_eq!(team, previous.current_team());
+            assert_eq!(previous.child(m), s);
+        }
+        assert_eq!(observations.result, Some(result));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::game::{GameHistory, State, Team, Move};
+use crate::protocol::GameResult;
+
+/// A handler that is notified about the progress of an
+/// observed game, e.g. for monitoring a bot in a tournament.
+/// Unlike a `GameClientDelegate`, it is never asked for moves.
+pub trait SpectatorDelegate {
+    /// Invoked once the server confirms that the room is observed.
+    fn on_observe(&mut self, _room_id: &str) {}
+
+    /// Invoked whenever the game state updates.
+    fn on_update_state(&mut self, _state: &State) {}
+
+    /// Invoked with the moves and states since the start of
+    /// the observation whenever a new state is recorded.
+    fn on_update_history(&mut self, _history: &GameHistory) {}
+
+    /// Invoked when the given team performed the given move,
+    /// resulting in the given state.
+    fn on_move(&mut self, _team: Team, _performed_move: &Move, _state: &State) {}
+
+    /// Invoked when the game ends.
+    fn on_game_end(&mut self, _result: &GameResult) {}
+}
+
+impl<D> SpectatorDelegate for Box<D> where D: SpectatorDelegate + ?Sized {
+    fn on_observe(&mut self, room_id: &str) {
+        (**self).on_observe(room_id)
+    }
+
+    fn on_update_state(&mut self, state: &State) {
+        (**self).on_update_state(state)
+    }
+
+    fn on_update_history(&mut self, history: &GameHistory) {
+        (**self).on_update_history(history)
+    }
+
+    fn on_move(&mut self, team: Team, performed_move: &Move, state: &State) {
+        (**self).on_move(team, performed_move, state)
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        (**self).on_game_end(result)
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::{Instant, Duration};
+
+/// The time available to the delegate for computing a move,
+/// measured from the arrival of the move request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeBudget {
+    received: Instant,
+    deadline: Instant,
+}
+
+impl TimeBudget {
+    /// The server's soft timeout for move requests.
+    pub const SOFT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// The default safety margin, accounting for network latency and serialization.
+    pub const DEFAULT_SAFETY_MARGIN: Duration = Duration::from_millis(300);
+
+    /// Creates a new time budget for a move request received at the given
+    /// instant, with the given time limit reduced by the given safety margin.
+    pub fn new(received: Instant, time_limit: Duration, safety_margin: Duration) -> Self {
+        Self { received, deadline: received + time_limit.saturating_sub(safety_margin) }
+    }
+
+    /// The instant at which the move request was received.
+    pub fn received(self) -> Instant { self.received }
+
+    /// The instant by which the move should be sent.
+    pub fn deadline(self) -> Instant { self.deadline }
+
+    /// The total time available for the move.
+    pub fn total(self) -> Duration { self.deadline - self.received }
+
+    /// The time elapsed since the move request was received.
+    pub fn elapsed(self) -> Duration { self.received.elapsed() }
+
+    /// The time remaining until the deadline.
+    pub fn remaining(self) -> Duration { self.deadline.saturating_duration_since(Instant::now()) }
+
+    /// Whether the deadline has passed.
+    pub fn is_expired(self) -> bool { Instant::now() >= self.deadline }
+}
+
+impl Default for TimeBudget {
+    fn default() -> Self {
+        Self::new(Instant::now(), Self::SOFT_TIMEOUT, Self::DEFAULT_SAFETY_MARGIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Instant, Duration};
+
+    use super::TimeBudget;
+
+    #[test]
+    fn test_deadline() {
+        let received = Instant::now();
+        let budget = TimeBudget::new(received, Duration::from_secs(2), Duration::from_millis(500));
+        assert_eq!(budget.deadline(), received + Duration::from_millis(1500));
+        assert_eq!(budget.total(), Duration::from_millis(1500));
+        assert!(budget.remaining() <= budget.total());
+
+        let expired = TimeBudget::new(received, Duration::from_millis(100), Duration::from_millis(200));
+        assert_eq!(expired.total(), Duration::ZERO);
+        assert!(expired.is_expired());
+    }
+}
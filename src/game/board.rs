@@ -1,17 +1,132 @@
-use std::{ops::{Index, IndexMut}, fmt, str::FromStr};
+use std::{ops::Index, fmt, str::FromStr};
 
 use arrayvec::ArrayVec;
+use rand::Rng;
 
 use crate::util::{Element, Error, Result};
 
-use super::{Field, BOARD_FIELDS, Vec2, Direct, BOARD_SIZE, Move, Doubled, Team};
+use super::{Field, FieldMut, BOARD_FIELDS, Vec2, Direct, BOARD_SIZE, Move, Doubled, Team, TEAMS, MAX_FISH, zobrist};
 
 // Ported from https://github.com/software-challenge/backend/blob/a3145a91749abb73ca5ffd426fd2a77d9a90967a/plugin/src/main/kotlin/sc/plugin2023/Board.kt
 
+/// The maximum number of holes in each half of a generated board.
+const MAX_HOLES_PER_HALF: usize = 5;
+
+/// The number of fish that the floes of each half of a generated board are drawn from.
+const FISH_PER_HALF: usize = BOARD_FIELDS;
+
+/// The fish count increases by one per this many values of the drawn number.
+const FISH_STEP: usize = 20;
+
+/// The number of hex directions.
+const DIRECTION_COUNT: usize = Vec2::<Doubled>::DIRECTIONS.len();
+
+/// The field values that can be stored on a board, referenced by `Index`.
+static FIELDS: [Field; MAX_FISH + 1 + TEAMS] = [
+    Field::EMPTY,
+    Field::with_fish(1),
+    Field::with_fish(2),
+    Field::with_fish(3),
+    Field::with_fish(4),
+    Field::with_penguin(Team::One),
+    Field::with_penguin(Team::Two),
+];
+
+/// The index of the neighbor in the given direction, if in bounds.
+const fn neighbor_index(index: usize, direction: usize) -> Option<usize> {
+    let delta = Vec2::<Doubled>::DIRECTIONS[direction];
+    let y = (index / BOARD_SIZE) as i32;
+    let x = (index % BOARD_SIZE) as i32 * 2 + y % 2;
+    let (nx, ny) = (x + delta.x, y + delta.y);
+    if nx < 0 || ny < 0 || ny >= BOARD_SIZE as i32 || nx / 2 >= BOARD_SIZE as i32 {
+        None
+    } else {
+        Some(ny as usize * BOARD_SIZE + (nx / 2) as usize)
+    }
+}
+
+/// Whether walking in the given direction increases the field index.
+const fn ascending(direction: usize) -> bool {
+    let delta = Vec2::<Doubled>::DIRECTIONS[direction];
+    delta.y > 0 || (delta.y == 0 && delta.x > 0)
+}
+
+/// The fields reachable by walking in each direction from each field, ignoring obstacles.
+const RAYS: [[u64; BOARD_FIELDS]; DIRECTION_COUNT] = {
+    let mut rays = [[0; BOARD_FIELDS]; DIRECTION_COUNT];
+    let mut direction = 0;
+    while direction < DIRECTION_COUNT {
+        let mut index = 0;
+        while index < BOARD_FIELDS {
+            let mut current = neighbor_index(index, direction);
+            while let Some(next) = current {
+                rays[direction][index] |= 1 << next;
+                current = neighbor_index(next, direction);
+            }
+            index += 1;
+        }
+        direction += 1;
+    }
+    rays
+};
+
+/// The hex neighbors of each field.
+const NEIGHBORS: [u64; BOARD_FIELDS] = {
+    let mut neighbors = [0; BOARD_FIELDS];
+    let mut index = 0;
+    while index < BOARD_FIELDS {
+        let mut direction = 0;
+        while direction < DIRECTION_COUNT {
+            if let Some(next) = neighbor_index(index, direction) {
+                neighbors[index] |= 1 << next;
+            }
+            direction += 1;
+        }
+        index += 1;
+    }
+    neighbors
+};
+
+/// An iterator over the indices of the set bits in a bitboard.
+#[derive(Debug, Clone, Copy)]
+struct Bits {
+    bits: u64,
+    ascending: bool,
+}
+
+impl Iterator for Bits {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.bits == 0 {
+            None
+        } else if self.ascending {
+            let index = self.bits.trailing_zeros() as usize;
+            self.bits &= self.bits - 1;
+            Some(index)
+        } else {
+            let index = 63 - self.bits.leading_zeros() as usize;
+            self.bits &= !(1 << index);
+            Some(index)
+        }
+    }
+}
+
 /// The 8x8 game board, a two-dimensional grid of ice floes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 
+/// Internally, the board is represented by bitboards, i.e. one
+/// bit per field, for every fish count and every team. Since the
+/// fields are thus not stored individually, the board cannot be
+/// indexed mutably: Instead of `board[c] = field`, write
+/// `*board.field_mut(c) = field` or `board.set(c, field)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Board {
-    fields: [Field; BOARD_FIELDS],
+    /// The fields with `i + 1` fish.
+    fish: [u64; MAX_FISH],
+    /// The fields occupied by the penguins of each team.
+    penguins: [u64; TEAMS],
+    /// The incrementally updated Zobrist hash of the fields.
+    hash: u64,
 }
 
 impl Default for Board {
@@ -22,11 +137,51 @@ impl Default for Board {
 
 impl Board {
     /// The empty board.
-    pub const EMPTY: Self = Self { fields: [Field::EMPTY; BOARD_FIELDS] };
+    pub const EMPTY: Self = Self { fish: [0; MAX_FISH], penguins: [0; TEAMS], hash: 0 };
 
     /// Creates a new board with the given fields.
+    /// 
+    /// Panics if a field carries more than `MAX_FISH` fish.
     pub const fn new(fields: [Field; BOARD_FIELDS]) -> Self {
-        Self { fields }
+        let mut board = Self::EMPTY;
+        let mut i = 0;
+        while i < BOARD_FIELDS {
+            board.set_index(i, Self::field_index_of(fields[i]));
+            i += 1;
+        }
+        board
+    }
+
+    /// Generates a random starting board using the algorithm of the official server.
+    /// The first half is generated field by field from a budget of remaining fish:
+    /// A number below the budget is drawn, yielding a hole if it is below the number
+    /// of remaining holes and otherwise a floe whose fish (subtracted from the budget)
+    /// grow with the number. Since the budget shrinks, most floes carry 1 or 2 fish.
+    /// The second half mirrors the first, i.e. the board is point-symmetric.
+    /// 
+    /// Pass a seeded random number generator for reproducible boards. Note that the
+    /// boards differ from the official server's for the same seed, since the server
+    /// uses a different random number generator.
+    // Ported from https://github.com/software-challenge/backend/blob/a3145a91749abb73ca5ffd426fd2a77d9a90967a/plugin/src/main/kotlin/sc/plugin2023/Board.kt (generateFields)
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let mut remaining_fish = FISH_PER_HALF;
+        let mut remaining_holes = MAX_HOLES_PER_HALF;
+        let mut board = Self::EMPTY;
+        for i in 0..BOARD_FIELDS / 2 {
+            // The budget cannot run out within a half (at least 9 fish always remain)
+            let n = rng.gen_range(0..remaining_fish);
+            let field = if n < remaining_holes {
+                remaining_holes -= 1;
+                Field::EMPTY
+            } else {
+                let fish = (n - remaining_holes) / FISH_STEP + 1;
+                remaining_fish -= fish;
+                Field::with_fish(fish)
+            };
+            board.set(Self::coords_for(i), field);
+            board.set(Self::coords_for(BOARD_FIELDS - 1 - i), field);
+        }
+        board
     }
 
     /// Checks whether the given coordinates are in bounds.
@@ -54,6 +209,42 @@ impl Board {
         Vec2::new((index % BOARD_SIZE) as i32, (index / BOARD_SIZE) as i32)
     }
 
+    /// The fields with at least one fish, i.e. the fields that can be moved onto.
+    fn floes(&self) -> u64 {
+        self.fish.iter().fold(0, |acc, &bits| acc | bits)
+    }
+
+    /// The index into `FIELDS` of the field at the given index.
+    const fn field_index(&self, index: usize) -> usize {
+        let bit = 1 << index;
+        let mut team = 0;
+        while team < TEAMS {
+            if self.penguins[team] & bit != 0 {
+                return MAX_FISH + 1 + team;
+            }
+            team += 1;
+        }
+        let mut i = 0;
+        while i < MAX_FISH {
+            if self.fish[i] & bit != 0 {
+                return i + 1;
+            }
+            i += 1;
+        }
+        0
+    }
+
+    /// The index into `FIELDS` of the given field, rejecting more than `MAX_FISH` fish.
+    const fn field_index_of(field: Field) -> usize {
+        match field.penguin() {
+            Some(team) => MAX_FISH + 1 + team.index(),
+            None => {
+                assert!(field.fish() <= MAX_FISH, "A field cannot carry more than MAX_FISH fish");
+                field.fish()
+            },
+        }
+    }
+
     /// Optionally fetches the field at the given position.
     pub fn get(&self, coords: impl Into<Vec2<Doubled>> + Copy) -> Option<Field> {
         if Self::in_bounds(coords.into()) {
@@ -63,28 +254,125 @@ impl Board {
         }
     }
 
+    /// Replaces the field at the given position.
+    /// 
+    /// Panics if the field carries more than `MAX_FISH` fish.
+    pub fn set(&mut self, coords: impl Into<Vec2<Doubled>>, field: Field) {
+        self.set_index(Self::index_for(coords), Self::field_index_of(field));
+    }
+
+    /// Mutably borrows the field at the given position. The board
+    /// is updated once the returned `FieldMut` is dropped.
+    pub fn field_mut(&mut self, coords: impl Into<Vec2<Doubled>>) -> FieldMut<'_> {
+        let coords = coords.into();
+        let field = self[coords];
+        FieldMut::new(self, coords, field)
+    }
+
+    /// Replaces the field at the given index by the one at the given index into `FIELDS`.
+    const fn set_index(&mut self, index: usize, field_index: usize) {
+        let bit = 1 << index;
+        self.hash ^= Self::field_hash(index, self.field_index(index)) ^ Self::field_hash(index, field_index);
+        let mut i = 0;
+        while i < MAX_FISH {
+            self.fish[i] &= !bit;
+            i += 1;
+        }
+        let mut team = 0;
+        while team < TEAMS {
+            self.penguins[team] &= !bit;
+            team += 1;
+        }
+        if field_index > MAX_FISH {
+            self.penguins[field_index - MAX_FISH - 1] |= bit;
+        } else if field_index > 0 {
+            self.fish[field_index - 1] |= bit;
+        }
+    }
+
+    /// The Zobrist key of the field with the given index into `FIELDS` at the given index.
+    const fn field_hash(index: usize, field_index: usize) -> u64 {
+        if field_index > MAX_FISH {
+            zobrist::penguin_key(index, field_index - MAX_FISH - 1)
+        } else if field_index > 0 {
+            zobrist::fish_key(index, field_index)
+        } else {
+            0
+        }
+    }
+
+    /// The Zobrist hash of the fields, i.e. of the fish counts and penguin positions.
+    pub fn zobrist_key(&self) -> u64 {
+        self.hash
+    }
+
+    /// Replaces the fish on the given field by a penguin, returning the number of fish.
+    pub fn place(&mut self, coords: impl Into<Vec2<Doubled>> + Copy, team: Team) -> usize {
+        let fish = self[coords].fish();
+        self.set(coords, Field::with_penguin(team));
+        fish
+    }
+
     /// Fetches the possible moves from a given position.
-    pub fn possible_moves_from<'a>(&'a self, coords: impl Into<Vec2<Doubled>>) -> impl Iterator<Item=Move> + 'a {
+    pub fn possible_moves_from(&self, coords: impl Into<Vec2<Doubled>>) -> impl Iterator<Item=Move> + '_ {
         let doubled: Vec2<Doubled> = coords.into();
-        Vec2::<Doubled>::DIRECTIONS
-            .into_iter()
-            .flat_map(move |v| (1..BOARD_SIZE as i32)
-                .map(move |n| Move::sliding(doubled, n * v))
-                .take_while(|c| self.get(c.to()).unwrap_or_default().fish() > 0))
+        let index = Self::index_for(doubled);
+        let floes = self.floes();
+        (0..DIRECTION_COUNT)
+            .flat_map(move |direction| {
+                let ray = RAYS[direction][index];
+                let blockers = ray & !floes;
+                let reachable = if blockers == 0 {
+                    ray
+                } else {
+                    let blocker = Bits { bits: blockers, ascending: ascending(direction) }.next().unwrap();
+                    ray & !(RAYS[direction][blocker] | 1 << blocker)
+                };
+                Bits { bits: reachable, ascending: ascending(direction) }
+            })
+            .map(move |i| Move::between(doubled, Self::coords_for(i)))
+    }
+
+    /// Whether a penguin on the given position could move anywhere.
+    pub fn can_move_from(&self, coords: impl Into<Vec2<Doubled>>) -> bool {
+        NEIGHBORS[Self::index_for(coords)] & self.floes() != 0
     }
 
     /// Fetches an iterator over the fields with coordinates.
-    pub fn fields(&self) -> impl Iterator<Item=(Vec2<Doubled>, Field)> {
-        self.fields
-            .into_iter()
-            .enumerate()
-            .map(|(i, f)| (Self::coords_for(i).into(), f))
+    pub fn fields(&self) -> impl Iterator<Item=(Vec2<Doubled>, Field)> + '_ {
+        (0..BOARD_FIELDS).map(|i| (Self::coords_for(i).into(), FIELDS[self.field_index(i)]))
+    }
+
+    /// Fetches the positions of the fields with exactly the given number of fish.
+    pub fn fields_with_fish(&self, fish: usize) -> impl Iterator<Item=Vec2<Doubled>> {
+        let bits = match fish {
+            0 => !(self.floes() | self.penguins.iter().fold(0, |acc, &bits| acc | bits)),
+            n if n <= MAX_FISH => self.fish[n - 1],
+            _ => 0,
+        };
+        Bits { bits, ascending: true }.map(|i| Self::coords_for(i).into())
     }
 
     /// Fetches the penguins on the board.
     pub fn penguins(&self) -> impl Iterator<Item=(Vec2<Doubled>, Team)> {
-        self.fields()
-            .filter_map(|(c, f)| f.penguin().map(|p| (c, p)))
+        let [one, two] = self.penguins;
+        Bits { bits: one | two, ascending: true }
+            .map(move |i| (Self::coords_for(i).into(), if one & (1 << i) != 0 { Team::One } else { Team::Two }))
+    }
+
+    /// Fetches the positions of the given team's penguins.
+    pub fn penguins_of(&self, team: Team) -> impl Iterator<Item=Vec2<Doubled>> {
+        Bits { bits: self.penguins[team.index()], ascending: true }.map(|i| Self::coords_for(i).into())
+    }
+
+    /// Counts all penguins on the board.
+    pub fn total_penguin_count(&self) -> usize {
+        self.penguins.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Counts the given team's penguins.
+    pub fn penguin_count(&self, team: Team) -> usize {
+        self.penguins[team.index()].count_ones() as usize
     }
 }
 
@@ -92,13 +380,7 @@ impl<V> Index<V> for Board where V: Copy + Into<Vec2<Doubled>> {
     type Output = Field;
 
     fn index(&self, index: V) -> &Field {
-        &self.fields[Self::index_for(index)]
-    }
-}
-
-impl<V> IndexMut<V> for Board where V: Copy + Into<Vec2<Doubled>> {
-    fn index_mut(&mut self, index: V) -> &mut Field {
-        &mut self.fields[Self::index_for(index)]
+        &FIELDS[self.field_index(Self::index_for(index))]
     }
 }
 
@@ -106,7 +388,7 @@ impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for y in 0..BOARD_SIZE {
             for x in 0..BOARD_SIZE {
-                write!(f, "{}", self.fields[y * BOARD_SIZE + x])?;
+                write!(f, "{}", self[Vec2::<Direct>::new(x as i32, y as i32)])?;
             }
             writeln!(f)?;
         }
@@ -114,18 +396,30 @@ impl fmt::Display for Board {
     }
 }
 
+impl TryFrom<ArrayVec<Field, BOARD_FIELDS>> for Board {
+    type Error = Error;
+
+    fn try_from(fields: ArrayVec<Field, BOARD_FIELDS>) -> Result<Self> {
+        if let Some(field) = fields.iter().find(|f| f.fish() > MAX_FISH) {
+            return Err(Error::from(format!("Board has field with too many fish: {}", field)));
+        }
+        Ok(Self::new(fields
+            .into_inner()
+            .map_err(|e| Error::from(format!("Board has wrong number of fields: {:?}", e)))?))
+    }
+}
+
 impl FromStr for Board {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        Ok(Self {
-            fields: s.lines()
-                .filter(|l| !l.is_empty())
-                .flat_map(|l| l.chars().map(|c| c.try_into()))
-                .collect::<Result<ArrayVec<Field, BOARD_FIELDS>>>()?
-                .into_inner()
-                .map_err(|e| Error::from(format!("Board has wrong number of fields: {:?}", e)))?
-        })
+        // Rows are separated by line breaks or, in the compact notation, by slashes
+        s.split(['\n', '/'])
+            .map(|l| l.trim_end_matches('\r'))
+            .filter(|l| !l.is_empty())
+            .flat_map(|l| l.chars().map(|c| c.try_into()))
+            .collect::<Result<ArrayVec<Field, BOARD_FIELDS>>>()?
+            .try_into()
     }
 }
 
@@ -133,13 +427,20 @@ impl TryFrom<&Element> for Board {
     type Error = Error;
 
     fn try_from(elem: &Element) -> Result<Self> {
-        Ok(Self {
-            fields: elem.childs_by_name("list")
-                .flat_map(|c| c.childs_by_name("field").map(|c| c.try_into()))
-                .collect::<Result<ArrayVec<Field, BOARD_FIELDS>>>()?
-                .into_inner()
-                .map_err(|e| Error::from(format!("Board has wrong number of fields: {:?}", e)))?
-        })
+        elem.childs_by_name("list")
+            .flat_map(|c| c.childs_by_name("field").map(|c| c.try_into()))
+            .collect::<Result<ArrayVec<Field, BOARD_FIELDS>>>()?
+            .try_into()
+    }
+}
+
+impl From<Board> for Element {
+    fn from(board: Board) -> Self {
+        Element::new("board")
+            .childs((0..BOARD_SIZE).map(|y| Element::new("list")
+                .childs((0..BOARD_SIZE).map(|x| board[Vec2::<Direct>::new(x as i32, y as i32)].into()))
+                .build()))
+            .build()
     }
 }
 
@@ -148,8 +449,23 @@ mod tests {
     use std::str::FromStr;
 
     use indoc::indoc;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::{util::Element, game::{Board, Team, Vec2, Field, Direct, Doubled, Move, BOARD_FIELDS, BOARD_SIZE, MAX_FISH}};
 
-    use crate::{util::Element, game::{Board, Team, Vec2, Field, Direct}};
+    /// The board described by the XML in `test_from_xml`.
+    fn board() -> Board {
+        Board::new([
+            3.into(), 2.into(), 1.into(), 1.into(), 4.into(), 3.into(), 2.into(), 3.into(),
+            3.into(), 2.into(), 2.into(), 3.into(), 1.into(), 1.into(), 2.into(), 1.into(),
+            1.into(), 2.into(), 2.into(), 1.into(), 1.into(), 2.into(), 1.into(), 1.into(),
+            2.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(),
+            1.into(), 1.into(), 1.into(), 1.into(), 2.into(), 1.into(), 1.into(), 1.into(),
+            1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), Team::One.into(), 1.into(),
+            1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(),
+            1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(), 1.into(),
+        ])
+    }
 
     #[test]
     fn test_from_xml() {
@@ -248,12 +564,24 @@ mod tests {
         ]));
     }
 
+    #[test]
+    fn test_to_xml() {
+        let xml = Element::from(board());
+        let rows: Vec<Vec<&str>> = xml.childs_by_name("list")
+            .map(|l| l.childs_by_name("field").map(|f| f.content()).collect())
+            .collect();
+        assert_eq!(rows.len(), BOARD_SIZE);
+        assert_eq!(rows[0], ["3", "2", "1", "1", "4", "3", "2", "3"]);
+        assert_eq!(rows[5], ["1", "1", "1", "1", "1", "1", "ONE", "1"]);
+        assert_eq!(Board::try_from(&xml).unwrap(), board());
+    }
+
     #[test]
     fn test_display_roundtrip() {
         let mut board = Board::EMPTY;
-        board[Vec2::<Direct>::new(2, 2)] = Field::with_fish(3);
-        board[Vec2::<Direct>::new(1, 0)] = Field::with_penguin(Team::One);
-        board[Vec2::<Direct>::new(1, 1)] = Field::with_penguin(Team::Two);
+        board.set(Vec2::<Direct>::new(2, 2), Field::with_fish(3));
+        board.set(Vec2::<Direct>::new(1, 0), Field::with_penguin(Team::One));
+        board.set(Vec2::<Direct>::new(1, 1), Field::with_penguin(Team::Two));
 
         assert_eq!(board.to_string(), indoc! {r#"
             0R000000
@@ -268,4 +596,87 @@ mod tests {
 
         assert_eq!(board.to_string().parse::<Board>().unwrap(), board);
     }
+
+    #[test]
+    fn test_possible_moves_from_matches_ray_walk() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let board = Board::new([(); BOARD_FIELDS].map(|_| match rng.gen_range(0..10) {
+                0 => Field::with_penguin(Team::One),
+                1 => Field::with_penguin(Team::Two),
+                n => Field::with_fish(n % (MAX_FISH + 1)),
+            }));
+            for (c, _) in board.fields() {
+                let expected: Vec<_> = Vec2::<Doubled>::DIRECTIONS
+                    .into_iter()
+                    .flat_map(|v| (1..BOARD_SIZE as i32)
+                        .map(move |n| Move::sliding(c, n * v))
+                        .take_while(|m| board.get(m.to()).unwrap_or_default().fish() > 0))
+                    .collect();
+                assert_eq!(board.possible_moves_from(c).collect::<Vec<_>>(), expected);
+                assert_eq!(board.can_move_from(c), !expected.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_mut() {
+        const BOARD: Board = Board::new([Field::with_fish(2); BOARD_FIELDS]);
+        let mut board = BOARD;
+        let c = Vec2::<Doubled>::new(3, 1);
+        assert_eq!(board.field_mut(c).place(Team::Two), 2);
+        assert_eq!(board[c], Field::with_penguin(Team::Two));
+        assert_ne!(board.zobrist_key(), BOARD.zobrist_key());
+        *board.field_mut(c) = Field::with_fish(2);
+        assert_eq!(board, BOARD);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot carry more than MAX_FISH fish")]
+    fn test_set_too_many_fish() {
+        let mut board = Board::EMPTY;
+        board.set(Vec2::<Doubled>::new(3, 1), Field::with_fish(MAX_FISH + 1));
+    }
+
+    #[test]
+    fn test_generate_seeded() {
+        // Guards against accidental changes to the generated distribution
+        assert_eq!(Board::generate(&mut StdRng::seed_from_u64(0)).to_string(), indoc! {r#"
+            21313231
+            30112121
+            22012201
+            11111111
+            11111111
+            10221022
+            12121103
+            13231312
+        "#});
+        assert_eq!(Board::generate(&mut StdRng::seed_from_u64(1)).to_string(), indoc! {r#"
+            32211122
+            10111212
+            21111121
+            01111111
+            11111110
+            12111112
+            21211101
+            22111223
+        "#});
+    }
+
+    #[test]
+    fn test_generate() {
+        for seed in 0..50 {
+            let board = Board::generate(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(board, Board::generate(&mut StdRng::seed_from_u64(seed)));
+            assert_eq!(board.penguins().count(), 0);
+            assert!(board.fields_with_fish(0).count() <= 10);
+            // The shrinking budget mostly leaves floes with few fish
+            assert!(board.fields_with_fish(1).count() + board.fields_with_fish(2).count() >= BOARD_FIELDS / 2);
+            for (c, f) in board.fields() {
+                // Point symmetry in doubled coordinates
+                let mirrored = Vec2::<Doubled>::new(2 * BOARD_SIZE as i32 - 1 - c.x, BOARD_SIZE as i32 - 1 - c.y);
+                assert_eq!(board[mirrored], f);
+            }
+        }
+    }
 }
@@ -9,3 +9,6 @@ pub const TEAMS: usize = 2;
 
 /// Number of penguins per team.
 pub const PENGUINS_PER_TEAM: usize = 4;
+
+/// The maximum number of fish on a field.
+pub const MAX_FISH: usize = 4;
@@ -40,10 +40,10 @@ impl Field {
     pub fn is_occupied(self) -> bool { self.penguin.is_some() }
 
     /// The number of fish on this field.
-    pub fn fish(self) -> usize { self.fish }
+    pub const fn fish(self) -> usize { self.fish }
 
     /// The penguin on this field.
-    pub fn penguin(self) -> Option<Team> { self.penguin }
+    pub const fn penguin(self) -> Option<Team> { self.penguin }
 
     /// Replaces the fish on this field by a penguin, returning the number of fish.
     pub fn place(&mut self, team: Team) -> usize {
@@ -100,3 +100,28 @@ impl TryFrom<&Element> for Field {
         })
     }
 }
+
+impl From<Field> for Element {
+    fn from(field: Field) -> Self {
+        let content = match field.penguin {
+            Some(team) => team.to_string(),
+            None => field.fish.to_string(),
+        };
+        Element::new("field").content(&content).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{util::Element, game::{Field, Team}};
+
+    #[test]
+    fn test_xml_roundtrip() {
+        for field in [Field::EMPTY, Field::with_fish(3), Field::with_penguin(Team::Two)] {
+            assert_eq!(Field::try_from(&Element::from(field)).unwrap(), field);
+        }
+        assert_eq!(Element::from(Field::with_penguin(Team::One)), Element::from_str("<field>ONE</field>").unwrap());
+    }
+}
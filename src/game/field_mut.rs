@@ -0,0 +1,39 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Board, Doubled, Field, Vec2};
+
+/// A mutable borrow of a field on the board. Since the board stores
+/// its fields as bitboards, changes are written back when dropped.
+#[derive(Debug)]
+pub struct FieldMut<'a> {
+    board: &'a mut Board,
+    coords: Vec2<Doubled>,
+    field: Field,
+}
+
+impl<'a> FieldMut<'a> {
+    /// Borrows the given field at the given position on the board.
+    pub(crate) fn new(board: &'a mut Board, coords: Vec2<Doubled>, field: Field) -> Self {
+        Self { board, coords, field }
+    }
+}
+
+impl Deref for FieldMut<'_> {
+    type Target = Field;
+
+    fn deref(&self) -> &Field {
+        &self.field
+    }
+}
+
+impl DerefMut for FieldMut<'_> {
+    fn deref_mut(&mut self) -> &mut Field {
+        &mut self.field
+    }
+}
+
+impl Drop for FieldMut<'_> {
+    fn drop(&mut self) {
+        self.board.set(self.coords, self.field);
+    }
+}
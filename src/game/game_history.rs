@@ -0,0 +1,120 @@
+use log::warn;
+
+use super::{Move, State, Team};
+
+/// The states and moves of a game since its start (or since
+/// the first state that was recorded).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GameHistory {
+    /// The recorded states in chronological order.
+    states: Vec<State>,
+    /// The moves leading to the states, i.e. `moves[i]` is the last move of `states[i + 1]`.
+    /// It is performed in `states[i]` unless intermediate states were not recorded.
+    moves: Vec<Move>,
+}
+
+impl GameHistory {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the given state, returning the move leading to it if it
+    /// follows the most recent state. States that are not newer than the
+    /// most recent one (e.g. repeated states) are ignored.
+    pub fn push(&mut self, state: State) -> Option<Move> {
+        let Some(previous) = self.states.last() else {
+            self.states.push(state);
+            return None;
+        };
+        if state.turn() <= previous.turn() {
+            return None;
+        }
+        if state.turn() != previous.turn() + 1 {
+            warn!("History skips from turn {} to {}", previous.turn(), state.turn());
+        }
+        let m = state.last_move()?;
+        self.states.push(state);
+        self.moves.push(m);
+        Some(m)
+    }
+
+    /// Fetches the recorded states in chronological order.
+    pub fn states(&self) -> &[State] { &self.states }
+
+    /// Fetches the moves between the recorded states in chronological order.
+    pub fn moves(&self) -> &[Move] { &self.moves }
+
+    /// Fetches the first recorded state.
+    pub fn initial_state(&self) -> Option<&State> { self.states.first() }
+
+    /// Fetches the most recent state.
+    pub fn current_state(&self) -> Option<&State> { self.states.last() }
+
+    /// Whether no state has been recorded yet.
+    pub fn is_empty(&self) -> bool { self.states.is_empty() }
+
+    /// Iterates the moves along with the teams that performed them, i.e. the
+    /// teams whose penguins stand on the moves' targets in the resulting states.
+    pub fn moves_with_teams(&self) -> impl Iterator<Item = (Move, Team)> + '_ {
+        self.moves.iter().zip(self.states.windows(2)).map(|(&m, s)| {
+            let team = s[1].board().get(m.to()).and_then(|f| f.penguin());
+            (m, team.unwrap_or_else(|| s[0].current_team()))
+        })
+    }
+
+    /// Iterates the moves performed by the given team.
+    pub fn moves_of(&self, team: Team) -> impl Iterator<Item = Move> + '_ {
+        self.moves_with_teams().filter(move |&(_, t)| t == team).map(|(m, _)| m)
+    }
+
+    /// Fetches the most recent move of the given team.
+    pub fn last_move_of(&self, team: Team) -> Option<Move> {
+        self.moves_of(team).last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::game::{Board, State, Team};
+
+    use super::GameHistory;
+
+    #[test]
+    fn test_push() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let mut state = State::new(Board::generate(&mut rng), 0, [0, 0], None, Team::One);
+        let mut history = GameHistory::new();
+        assert_eq!(history.push(state), None);
+        assert_eq!(history.push(state), None);
+
+        let mut moves = Vec::new();
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            moves.push((m, state.current_team()));
+            state.perform(m);
+            assert_eq!(history.push(state), Some(m));
+            assert_eq!(history.push(state), None);
+        }
+
+        assert_eq!(history.moves_with_teams().collect::<Vec<_>>(), moves);
+        assert_eq!(history.states().len(), moves.len() + 1);
+        assert_eq!(history.current_state(), Some(&state));
+        assert_eq!(history.last_move_of(Team::Two), moves.iter().rev().find(|(_, t)| *t == Team::Two).map(|(m, _)| *m));
+        let replayed = history.moves().iter().fold(*history.initial_state().unwrap(), |s, &m| s.child(m));
+        assert_eq!(replayed, state);
+    }
+
+    #[test]
+    fn test_moves_with_teams_after_gap() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let initial = State::new(Board::generate(&mut rng), 0, [0, 0], None, Team::One);
+        let first = initial.child(initial.possible_moves()[0]);
+        let second = first.child(first.possible_moves()[0]);
+        let mut history = GameHistory::new();
+        history.push(initial);
+        history.push(second);
+        assert_eq!(history.moves_with_teams().collect::<Vec<_>>(), vec![(second.last_move().unwrap(), Team::Two)]);
+    }
+}
@@ -1,15 +1,28 @@
 mod board;
 mod constants;
 mod field;
+mod field_mut;
+mod game_history;
 mod r#move;
+mod perft;
+mod perft_position;
+mod rule_violation;
 mod state;
 mod team;
+mod undo;
 mod vec2;
+mod zobrist;
 
 pub use board::*;
 pub use constants::*;
 pub use field::*;
+pub use field_mut::*;
+pub use game_history::*;
 pub use r#move::*;
+pub use perft::*;
+pub use perft_position::*;
+pub use rule_violation::*;
 pub use state::*;
 pub use team::*;
+pub use undo::*;
 pub use vec2::*;
@@ -1,10 +1,64 @@
 // Port of https://github.com/software-challenge/backend/blob/a3145a91749abb73ca5ffd426fd2a77d9a90967a/plugin/src/main/kotlin/sc/plugin2023/Move.kt
 
-use std::fmt;
+use std::{fmt, str::FromStr};
 
-use crate::util::{Element, Error, Result};
+use crate::util::{Element, ElementBuilder, Error, Result};
 
-use super::{Vec2, Doubled};
+use super::{Board, Vec2, Doubled, Direct, BOARD_SIZE};
+
+/// The compass names of the directions on the hex board, with north
+/// pointing towards the first row (i.e. decreasing y-coordinates).
+const COMPASS_DIRECTIONS: [(&str, Vec2<Doubled>); 6] = [
+    ("E", Vec2::new(2, 0)),
+    ("W", Vec2::new(-2, 0)),
+    ("NE", Vec2::new(1, -1)),
+    ("NW", Vec2::new(-1, -1)),
+    ("SE", Vec2::new(1, 1)),
+    ("SW", Vec2::new(-1, 1)),
+];
+
+/// Formats a field as its column letter (`A` to `H`, the direct
+/// x-coordinate) followed by its row number (`1` to `8`), e.g. `A3`.
+fn format_field(c: Vec2<Doubled>) -> String {
+    let c = c.to_direct();
+    format!("{}{}", (b'A' + c.x as u8) as char, c.y + 1)
+}
+
+/// Parses a field in the format produced by `format_field`.
+fn parse_field(s: &str) -> Result<Vec2<Doubled>> {
+    let mut chars = s.chars();
+    let column = chars.next()
+        .map(|c| c.to_ascii_uppercase())
+        .filter(|c| ('A'..='H').contains(c))
+        .ok_or_else(|| Error::Custom(format!("Invalid column in field {}", s)))?;
+    let row: i32 = chars.as_str().parse()?;
+    if !(1..=BOARD_SIZE as i32).contains(&row) {
+        return Err(Error::Custom(format!("Invalid row in field {}", s)));
+    }
+    Ok(Vec2::<Direct>::new(column as i32 - 'A' as i32, row - 1).to_doubled())
+}
+
+/// Parses a position in doubled coordinates as formatted by `Vec2`'s `Display`, e.g. `(3, 5)`.
+fn parse_doubled(s: &str) -> Result<Vec2<Doubled>> {
+    let (x, y) = s.trim()
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .and_then(|s| s.split_once(','))
+        .ok_or_else(|| Error::Custom(format!("Invalid coordinates {}", s)))?;
+    Ok(Vec2::new(x.trim().parse()?, y.trim().parse()?))
+}
+
+/// Parses a compass direction with an optional distance (defaulting to 1), e.g. `NE2`.
+fn parse_direction(s: &str) -> Result<Vec2<Doubled>> {
+    let split = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let (name, distance) = s.split_at(split);
+    let direction = COMPASS_DIRECTIONS.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, v)| v)
+        .ok_or_else(|| Error::Custom(format!("Invalid direction {}", name)))?;
+    let distance: i32 = if distance.is_empty() { 1 } else { distance.parse()? };
+    Ok(direction * distance)
+}
 
 // Ported from https://github.com/software-challenge/backend/blob/a3145a91749abb73ca5ffd426fd2a77d9a90967a/plugin/src/main/kotlin/sc/plugin2023/Move.kt
 
@@ -43,6 +97,35 @@ impl Move {
 
     /// The target position of the penguin.
     pub fn to(self) -> Vec2<Doubled> { self.to }
+
+    /// Formats the move in algebraic notation, i.e. placements as the target
+    /// field (e.g. `A3`) and slides as the source field followed by the compass
+    /// direction and distance (e.g. `A3 NE2`). Fields are given as column letter
+    /// and row number in direct coordinates. Slides that are not straight are
+    /// formatted as the source and target fields (e.g. `A3-C4`). Moves involving
+    /// positions that are not fields of the board fall back to doubled coordinates.
+    pub fn notation(self) -> String {
+        if !self.from.into_iter().chain([self.to]).all(|c| Board::in_bounds(c) && c.to_direct().to_doubled() == c) {
+            return self.to_string();
+        }
+        match self.from {
+            Some(from) => match (self.to - from).direction_and_distance() {
+                Some((direction, distance)) => {
+                    let (name, _) = COMPASS_DIRECTIONS.iter().find(|&&(_, v)| v == direction).unwrap();
+                    format!("{} {}{}", format_field(from), name, distance)
+                },
+                None => format!("{}-{}", format_field(from), format_field(self.to)),
+            },
+            None => format_field(self.to),
+        }
+    }
+
+    /// Serializes the move's positions into an element with the given name.
+    pub(crate) fn element(self, name: &str) -> ElementBuilder<'_> {
+        Element::new(name)
+            .option_child(self.from.map(|v| Element::new("from").attribute("x", v.x).attribute("y", v.y)))
+            .child(Element::new("to").attribute("x", self.to.x).attribute("y", self.to.y))
+    }
 }
 
 impl fmt::Display for Move {
@@ -55,6 +138,31 @@ impl fmt::Display for Move {
     }
 }
 
+impl FromStr for Move {
+    type Err = Error;
+
+    /// Parses a move either in doubled coordinates as formatted by `Display`
+    /// (e.g. `(3, 5) -> (7, 5)` or `-> (3, 5)`) or in the algebraic notation
+    /// produced by `notation` (e.g. `A3`, `A3 NE2` or `A3-C4`).
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some((from, to)) = s.split_once("->") {
+            let from = from.trim();
+            let from = if from.is_empty() { None } else { Some(parse_doubled(from)?) };
+            return Ok(Self::new(from, parse_doubled(to)?));
+        }
+        // Fields are separated by a dash, directions (which may look like fields, e.g. `E2`) by whitespace
+        if let Some((from, to)) = s.split_once('-') {
+            return Ok(Self::between(parse_field(from.trim())?, parse_field(to.trim())?));
+        }
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            [to] => Ok(Self::placing(parse_field(to)?)),
+            [from, direction] => Ok(Self::sliding(parse_field(from)?, parse_direction(direction)?)),
+            _ => Err(Error::Custom(format!("Invalid move notation {}", s))),
+        }
+    }
+}
+
 impl TryFrom<&Element> for Move {
     type Error = Error;
 
@@ -68,10 +176,8 @@ impl TryFrom<&Element> for Move {
 
 impl From<Move> for Element {
     fn from(m: Move) -> Self {
-        Element::new("data")
+        m.element("data")
             .attribute("class", "move")
-            .option_child(m.from.map(|v| Element::new("from").attribute("x", v.x).attribute("y", v.y)))
-            .child(Element::new("to").attribute("x", m.to.x).attribute("y", m.to.y))
             .build()
     }
 }
@@ -83,7 +189,7 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::{util::Element, game::{Move, Vec2}};
+    use crate::{util::Element, game::{Move, Vec2, Doubled, Direct, BOARD_SIZE}};
 
     #[test]
     fn test_place_from_xml() {
@@ -122,4 +228,46 @@ mod tests {
             </data>
         "#}).unwrap());
     }
+
+    #[test]
+    fn test_from_str() {
+        let slide = Move::between(Vec2::<Doubled>::new(3, 5), Vec2::<Doubled>::new(7, 5));
+        assert_eq!("(3, 5) -> (7, 5)".parse::<Move>().unwrap(), slide);
+        assert_eq!("B6 E2".parse::<Move>().unwrap(), slide);
+        assert_eq!("b6-d6".parse::<Move>().unwrap(), slide);
+        assert_eq!("-> (0, 2)".parse::<Move>().unwrap(), Move::placing(Vec2::<Doubled>::new(0, 2)));
+        assert_eq!("A3".parse::<Move>().unwrap(), Move::placing(Vec2::<Doubled>::new(0, 2)));
+        assert_eq!("A3 NE".parse::<Move>().unwrap(), Move::between(Vec2::<Doubled>::new(0, 2), Vec2::<Doubled>::new(1, 1)));
+        assert_eq!("A3 SE2".parse::<Move>().unwrap(), Move::between(Vec2::<Doubled>::new(0, 2), Vec2::<Doubled>::new(2, 4)));
+
+        for invalid in ["", "I1", "A0", "A9", "A3 N2", "A3 NE2 W", "(3, 5) -> 7, 5", "A3-"] {
+            assert!(invalid.parse::<Move>().is_err(), "{} should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn test_notation() {
+        assert_eq!(Move::placing(Vec2::<Doubled>::new(0, 2)).notation(), "A3");
+        assert_eq!(Move::between(Vec2::<Doubled>::new(3, 5), Vec2::<Doubled>::new(7, 5)).notation(), "B6 E2");
+        assert_eq!(Move::between(Vec2::<Doubled>::new(14, 0), Vec2::<Doubled>::new(11, 3)).notation(), "H1 SW3");
+        assert_eq!(Move::between(Vec2::<Doubled>::new(0, 0), Vec2::<Doubled>::new(1, 3)).notation(), "A1-A4");
+        assert_eq!(Move::placing(Vec2::<Doubled>::new(16, 0)).notation(), "-> (16, 0)");
+    }
+
+    #[test]
+    fn test_notation_roundtrip() {
+        let fields: Vec<_> = (0..BOARD_SIZE as i32)
+            .flat_map(|y| (0..BOARD_SIZE as i32).map(move |x| Vec2::<Direct>::new(x, y).to_doubled()))
+            .collect();
+        for &to in &fields {
+            let placement = Move::placing(to);
+            assert_eq!(placement.notation().parse::<Move>().unwrap(), placement);
+            assert_eq!(placement.to_string().parse::<Move>().unwrap(), placement);
+            for &from in &fields {
+                let slide = Move::between(from, to);
+                assert_eq!(slide.notation().parse::<Move>().unwrap(), slide, "Roundtrip of {}", slide.notation());
+                assert_eq!(slide.to_string().parse::<Move>().unwrap(), slide);
+            }
+        }
+    }
 }
@@ -0,0 +1,215 @@
+use super::{Move, State};
+
+/// Counts the leaf nodes of the game tree of the given depth below the
+/// given state, i.e. the number of distinct move sequences of that length.
+/// Branches ending in a finished game before reaching the depth are not counted.
+/// Used to verify the correctness and speed of the move generator.
+pub fn perft(state: &mut State, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = state.possible_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.into_iter()
+        .map(|m| {
+            let undo = state.perform(m);
+            let nodes = perft(state, depth - 1);
+            state.unperform(undo);
+            nodes
+        })
+        .sum()
+}
+
+/// Counts the leaf nodes below each possible move, which helps
+/// with pinpointing the move at which two move generators disagree.
+pub fn perft_divide(state: &mut State, depth: usize) -> Vec<(Move, u64)> {
+    state.possible_moves()
+        .into_iter()
+        .map(|m| {
+            let undo = state.perform(m);
+            let nodes = perft(state, depth.saturating_sub(1));
+            state.unperform(undo);
+            (m, nodes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{State, BOARD_SIZE, PENGUINS_PER_TEAM, PERFT_POSITIONS};
+
+    use super::{perft, perft_divide};
+
+    /// The directions in doubled coordinates.
+    const NAIVE_DIRECTIONS: [(i32, i32); 6] = [(-2, 0), (-1, -1), (1, -1), (2, 0), (1, 1), (-1, 1)];
+
+    /// A deliberately naive implementation of the rules, independent of `Board`
+    /// and `State`, for cross-checking the reference node counts. The fields are
+    /// stored as the characters of the compact notation and the moves are found
+    /// by walking the rays field by field.
+    #[derive(Clone)]
+    struct NaiveState {
+        fields: Vec<Vec<char>>,
+        turn: usize,
+        start_team: char,
+    }
+
+    /// Doubled coordinates in the naive rules.
+    type NaiveCoords = (i32, i32);
+
+    /// A move in the naive rules, as source (if any) and target.
+    type NaiveMove = (Option<NaiveCoords>, NaiveCoords);
+
+    impl NaiveState {
+        /// Parses the board, starting team and turn from the compact notation.
+        fn parse(notation: &str) -> Self {
+            let parts: Vec<_> = notation.split_whitespace().collect();
+            Self {
+                fields: parts[0].split('/').map(|row| row.chars().collect()).collect(),
+                turn: parts[2].parse().unwrap(),
+                start_team: parts[1].chars().next().unwrap(),
+            }
+        }
+
+        /// The field at the given doubled coordinates, if on the board.
+        fn field(&self, x: i32, y: i32) -> Option<char> {
+            if x < 0 || y < 0 || y >= BOARD_SIZE as i32 || x / 2 >= BOARD_SIZE as i32 {
+                None
+            } else {
+                Some(self.fields[y as usize][x as usize / 2])
+            }
+        }
+
+        /// The doubled coordinates of the given team's penguins.
+        fn penguins(&self, team: char) -> Vec<NaiveCoords> {
+            (0..BOARD_SIZE as i32)
+                .flat_map(|y| (0..BOARD_SIZE as i32).map(move |x| (2 * x + y % 2, y)))
+                .filter(|&(x, y)| self.field(x, y) == Some(team))
+                .collect()
+        }
+
+        /// The targets reachable from the given field.
+        fn targets(&self, (x, y): NaiveCoords) -> Vec<NaiveCoords> {
+            let mut targets = Vec::new();
+            for (dx, dy) in NAIVE_DIRECTIONS {
+                let (mut tx, mut ty) = (x + dx, y + dy);
+                while matches!(self.field(tx, ty), Some('1'..='4')) {
+                    targets.push((tx, ty));
+                    tx += dx;
+                    ty += dy;
+                }
+            }
+            targets
+        }
+
+        /// Whether the given team has placed all penguins and none can move.
+        fn immovable(&self, team: char) -> bool {
+            let penguins = self.penguins(team);
+            penguins.len() == PENGUINS_PER_TEAM && penguins.iter().all(|&p| self.targets(p).is_empty())
+        }
+
+        /// The team to move, skipping a team that cannot move.
+        fn current_team(&self) -> char {
+            let other = |t| if t == 'R' { 'B' } else { 'R' };
+            let team = if self.turn.is_multiple_of(2) { self.start_team } else { other(self.start_team) };
+            if self.immovable(team) { other(team) } else { team }
+        }
+
+        /// The possible moves of the current team as source (if any) and target.
+        fn moves(&self) -> Vec<NaiveMove> {
+            let team = self.current_team();
+            let penguins = self.penguins(team);
+            if penguins.len() < PENGUINS_PER_TEAM {
+                (0..BOARD_SIZE as i32)
+                    .flat_map(|y| (0..BOARD_SIZE as i32).map(move |x| (2 * x + y % 2, y)))
+                    .filter(|&(x, y)| self.field(x, y) == Some('1'))
+                    .map(|to| (None, to))
+                    .collect()
+            } else if self.immovable(team) {
+                Vec::new()
+            } else {
+                penguins.into_iter().flat_map(|from| self.targets(from).into_iter().map(move |to| (Some(from), to))).collect()
+            }
+        }
+
+        /// The state after the given move.
+        fn child(&self, (from, to): NaiveMove) -> Self {
+            let mut child = self.clone();
+            let team = self.current_team();
+            if let Some((x, y)) = from {
+                child.fields[y as usize][x as usize / 2] = '0';
+            }
+            child.fields[to.1 as usize][to.0 as usize / 2] = team;
+            child.turn += 1;
+            child
+        }
+
+        /// Counts the leaf nodes of the given depth.
+        fn perft(&self, depth: usize) -> u64 {
+            if depth == 0 {
+                return 1;
+            }
+            self.moves().into_iter().map(|m| self.child(m).perft(depth - 1)).sum()
+        }
+    }
+
+    /// Counts the leaf nodes by copying states rather than undoing moves.
+    fn copying_perft(state: &State, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        state.possible_moves().into_iter().map(|m| copying_perft(&state.child(m), depth - 1)).sum()
+    }
+
+    #[test]
+    fn test_reference_positions() {
+        for position in PERFT_POSITIONS {
+            let mut state = position.state();
+            for depth in (1..=position.max_depth()).filter(|&d| position.nodes_at(d).unwrap() <= 100_000) {
+                assert_eq!(Some(perft(&mut state, depth)), position.nodes_at(depth), "Perft of {} at depth {}", position.name(), depth);
+            }
+            assert_eq!(state, position.state());
+        }
+    }
+
+    #[test]
+    fn test_reference_positions_match_naive_rules() {
+        for position in PERFT_POSITIONS {
+            let naive = NaiveState::parse(position.notation());
+            for depth in (1..=position.max_depth()).filter(|&d| position.nodes_at(d).unwrap() <= 100_000) {
+                assert_eq!(Some(naive.perft(depth)), position.nodes_at(depth), "Naive perft of {} at depth {}", position.name(), depth);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "slow, run in release mode"]
+    fn test_all_reference_positions_match_naive_rules() {
+        for position in PERFT_POSITIONS {
+            let naive = NaiveState::parse(position.notation());
+            for depth in 1..=position.max_depth() {
+                assert_eq!(Some(naive.perft(depth)), position.nodes_at(depth), "Naive perft of {} at depth {}", position.name(), depth);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_copying_perft() {
+        for position in PERFT_POSITIONS {
+            let mut state = position.state();
+            assert_eq!(perft(&mut state, 3), copying_perft(&state, 3), "Perft of {}", position.name());
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        for position in PERFT_POSITIONS {
+            let mut state = position.state();
+            let divided = perft_divide(&mut state, 2);
+            assert_eq!(divided.len(), state.possible_moves().len());
+            assert_eq!(divided.iter().map(|&(_, n)| n).sum::<u64>(), position.nodes_at(2).unwrap());
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::State;
+
+/// A reference position with known perft node counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftPosition {
+    name: &'static str,
+    notation: &'static str,
+    nodes: &'static [u64],
+}
+
+impl PerftPosition {
+    /// Creates a new reference position from a state in compact
+    /// notation. The node counts are given for the depths 1, 2, 3 and so on.
+    pub const fn new(name: &'static str, notation: &'static str, nodes: &'static [u64]) -> Self {
+        Self { name, notation, nodes }
+    }
+
+    /// Fetches the name of the position.
+    pub fn name(&self) -> &'static str { self.name }
+
+    /// Fetches the state of the position in compact notation.
+    pub fn notation(&self) -> &'static str { self.notation }
+
+    /// Fetches the known node counts, starting at depth 1.
+    pub fn nodes(&self) -> &'static [u64] { self.nodes }
+
+    /// Fetches the known node count at the given depth.
+    pub fn nodes_at(&self, depth: usize) -> Option<u64> {
+        match depth {
+            0 => Some(1),
+            d => self.nodes.get(d - 1).copied(),
+        }
+    }
+
+    /// Fetches the maximum depth with a known node count.
+    pub fn max_depth(&self) -> usize { self.nodes.len() }
+
+    /// Creates the game state of this position.
+    pub fn state(&self) -> State {
+        self.notation.parse().expect("Invalid reference position")
+    }
+}
+
+/// Reference positions covering the placement phase, the transition
+/// to the movement phase, the movement phase and skipped teams.
+/// 
+/// The node counts were computed with a deliberately naive implementation of
+/// the rules that walks the rays field by field on a plain grid, independently
+/// of `Board` and `State` (see `NaiveState` in the tests of `perft`). The tests
+/// check the counts up to 100 000 nodes, all counts are checked by an ignored
+/// test (`cargo test --release -- --ignored`).
+pub const PERFT_POSITIONS: [PerftPosition; 6] = [
+    PerftPosition::new(
+        "start",
+        "10031114/10123120/24131122/14341310/01314341/22113142/02132101/41113001 R 0 0 0 -",
+        &[26, 650, 15_600, 358_800, 7_893_600],
+    ),
+    PerftPosition::new(
+        "placement",
+        "4B203312/12421224/11R04142/02021321/R2312020/24140111/422R2421/2B330214 R 5 3 2 -",
+        &[15, 210, 2_730, 108_078, 3_359_216],
+    ),
+    PerftPosition::new(
+        "last placement",
+        "3241B341/14144133/11332B43/4303B342/243R3034/341233R1/3314414R/1431R423 R 7 4 3 -",
+        &[13, 633, 27_335, 1_167_501, 45_584_179],
+    ),
+    PerftPosition::new(
+        "midgame",
+        "B401300B/10R31131/03R2304B/03R01002/20211130/0B030030/R0113310/21031142 R 20 14 17 -",
+        &[21, 280, 4_906, 57_920, 874_114],
+    ),
+    PerftPosition::new(
+        "endgame",
+        "13BR0030/10000B03/0440000R/0R001100/00001002/04303002/3B0100R0/4310B031 R 40 44 42 -",
+        &[6, 41, 192, 977, 3_466],
+    ),
+    PerftPosition::new(
+        "skipped team",
+        "R0401R04/B0000100/0B00100B/00021000/10412000/20100034/4110R0R0/4140B000 R 41 54 49 -",
+        &[3, 5, 6, 0],
+    ),
+];
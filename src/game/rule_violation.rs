@@ -0,0 +1,46 @@
+use std::fmt;
+
+use super::{Vec2, Doubled};
+
+/// A reason why a move is illegal, mirroring the server's move mistakes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleViolation {
+    /// The game is already over.
+    GameOver,
+    /// The given position is outside the board or, in doubled
+    /// coordinates, does not denote a field (since its parity is wrong).
+    OutOfBounds(Vec2<Doubled>),
+    /// A penguin was placed although the team has placed all of its penguins.
+    PlacingAfterPlacementPhase,
+    /// A penguin was moved before the team placed all of its penguins.
+    MovingDuringPlacementPhase,
+    /// A penguin was placed on a field that does not hold exactly one fish.
+    PlacementNotOnSingleFish(Vec2<Doubled>),
+    /// The start field does not hold a penguin of the current team.
+    NotOwnPenguin(Vec2<Doubled>),
+    /// The start and target field are the same.
+    NoMovement,
+    /// The target is not in a straight line from the start.
+    NotStraight,
+    /// The target field is occupied by a penguin.
+    DestinationOccupied(Vec2<Doubled>),
+    /// The path to the target crosses a hole or a penguin.
+    PathBlocked(Vec2<Doubled>),
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameOver => write!(f, "The game is already over"),
+            Self::OutOfBounds(c) => write!(f, "{} is out of bounds", c),
+            Self::PlacingAfterPlacementPhase => write!(f, "All penguins have already been placed"),
+            Self::MovingDuringPlacementPhase => write!(f, "Penguins can only move once all have been placed"),
+            Self::PlacementNotOnSingleFish(c) => write!(f, "Penguins can only be placed on fields with one fish, {} is not", c),
+            Self::NotOwnPenguin(c) => write!(f, "{} does not hold an own penguin", c),
+            Self::NoMovement => write!(f, "The penguin has to move"),
+            Self::NotStraight => write!(f, "Penguins can only move in straight lines"),
+            Self::DestinationOccupied(c) => write!(f, "{} is occupied", c),
+            Self::PathBlocked(c) => write!(f, "The path is blocked at {}", c),
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}, str::FromStr};
 
 use arrayvec::ArrayVec;
 
 use crate::util::{Element, Error, Result};
 
-use super::{Board, Move, Team, PENGUINS_PER_TEAM, TEAMS, Vec2, Field, Doubled, BOARD_FIELDS};
+use super::{Board, Move, Team, PENGUINS_PER_TEAM, TEAMS, Vec2, Field, Doubled, BOARD_FIELDS, Undo, RuleViolation, zobrist};
 
 // Ported from https://github.com/software-challenge/backend/blob/a3145a91749abb73ca5ffd426fd2a77d9a90967a/plugin/src/main/kotlin/sc/plugin2023/GameState.kt
 
@@ -24,6 +24,11 @@ pub struct State {
 }
 
 impl State {
+    /// Creates a new state with the given properties.
+    pub fn new(board: Board, turn: usize, fish: [usize; TEAMS], last_move: Option<Move>, start_team: Team) -> Self {
+        Self { board, turn, fish, last_move, start_team }
+    }
+
     /// Fetches the board.
     pub fn board(&self) -> &Board { &self.board }
 
@@ -39,9 +44,23 @@ impl State {
     /// Fetches the starting team.
     pub fn start_team(&self) -> Team { self.start_team }
 
+    /// The Zobrist hash of the position, covering the fields, the side to move
+    /// (as computed by `current_team_from_turn`) and the placement phase. The
+    /// board part is updated incrementally by `perform` and `unperform`.
+    pub fn zobrist_key(&self) -> u64 {
+        let mut key = self.board.zobrist_key();
+        if self.current_team_from_turn() == Team::Two {
+            key ^= zobrist::SIDE_KEY;
+        }
+        if self.board.total_penguin_count() < PENGUINS_PER_TEAM * TEAMS {
+            key ^= zobrist::PLACEMENT_KEY;
+        }
+        key
+    }
+
     /// The current team, computed from the starting team and the turn.
     pub fn current_team_from_turn(&self) -> Team {
-        self.start_team.opponent_if(|_| self.turn % 2 != 0)
+        self.start_team.opponent_if(|_| !self.turn.is_multiple_of(2))
     }
 
     /// Whether the given team cannot move.
@@ -52,10 +71,7 @@ impl State {
         if penguins.len() == PENGUINS_PER_TEAM * team.map_or(TEAMS, |_| 1) {
             penguins
                 .into_iter()
-                .all(|(c, _)| c
-                    .hex_neighbors()
-                    .into_iter()
-                    .all(|n| self.board.get(n).unwrap_or_default().fish() == 0))
+                .all(|(c, _)| !self.board.can_move_from(c))
         } else {
             false
         }
@@ -69,13 +85,13 @@ impl State {
     /// The current team's fields.
     pub fn current_pieces(&self) -> impl Iterator<Item=(Vec2<Doubled>, Field)> {
         let team = self.current_team();
-        self.board.fields()
-            .filter(move |(_, f)| f.penguin() == Some(team))
+        self.board.penguins_of(team)
+            .map(move |c| (c, Field::with_penguin(team)))
     }
 
     /// Whether the current team has placed all of its penguins.
     pub fn penguins_placed(&self) -> bool {
-        self.current_pieces().count() == PENGUINS_PER_TEAM
+        self.board.penguin_count(self.current_team()) == PENGUINS_PER_TEAM
     }
 
     /// Whether the game is over.
@@ -103,31 +119,95 @@ impl State {
                 .flat_map(|(c, _)| self.board.possible_moves_from(c))
                 .collect()
         } else {
-            self.board.fields()
-                .filter(|(_, f)| f.fish() == 1)
-                .map(|(c, _)| Move::placing(c))
+            self.board.fields_with_fish(1)
+                .map(Move::placing)
                 .collect()
         }
     }
 
-    /// Performs the given move.
-    pub fn perform(&mut self, m: Move) {
+    /// Checks whether the given move is legal in this state.
+    pub fn validate(&self, m: Move) -> std::result::Result<(), RuleViolation> {
+        if self.is_over() {
+            return Err(RuleViolation::GameOver);
+        }
         let to = m.to();
+        for c in m.from().into_iter().chain([to]) {
+            // Doubled coordinates of the wrong parity would silently map to a neighboring field
+            if !Board::in_bounds(c) || c.to_direct().to_doubled() != c {
+                return Err(RuleViolation::OutOfBounds(c));
+            }
+        }
         let team = self.current_team();
+        let placed = self.penguins_placed();
         if let Some(from) = m.from() {
-            // Prepare penguin slide
-            debug_assert!(self.board[from].penguin() == Some(team), "Wrong color");
-            debug_assert!(self.current_pieces().count() >= PENGUINS_PER_TEAM, "Cannot slide until all penguins have been placed");
-            debug_assert!((to - from).straight(), "Can only move in straight lines");
-            self.board[from] = Field::EMPTY;
+            if !placed {
+                return Err(RuleViolation::MovingDuringPlacementPhase);
+            }
+            if self.board[from].penguin() != Some(team) {
+                return Err(RuleViolation::NotOwnPenguin(from));
+            }
+            let delta = to - from;
+            if delta == Vec2::ZERO {
+                return Err(RuleViolation::NoMovement);
+            }
+            let (step, distance) = delta.direction_and_distance().ok_or(RuleViolation::NotStraight)?;
+            if self.board[to].is_occupied() {
+                return Err(RuleViolation::DestinationOccupied(to));
+            }
+            for n in 1..=distance {
+                let c = from + n * step;
+                if self.board[c].fish() == 0 {
+                    return Err(RuleViolation::PathBlocked(c));
+                }
+            }
         } else {
-            // Prepare penguin placement
-            debug_assert!(self.current_pieces().count() < PENGUINS_PER_TEAM, "Cannot place after all penguins have been placed");
-            debug_assert!(self.board[to].fish() == 1, "Cannot place on more than one fish");
+            if placed {
+                return Err(RuleViolation::PlacingAfterPlacementPhase);
+            }
+            if self.board[to].is_occupied() {
+                return Err(RuleViolation::DestinationOccupied(to));
+            }
+            if self.board[to].fish() != 1 {
+                return Err(RuleViolation::PlacementNotOnSingleFish(to));
+            }
+        }
+        Ok(())
+    }
+
+    /// Performs the given move if it is legal, returning a token for reverting it.
+    pub fn try_perform(&mut self, m: Move) -> std::result::Result<Undo, RuleViolation> {
+        self.validate(m)?;
+        Ok(self.perform(m))
+    }
+
+    /// Performs the given move without checking its legality (see `try_perform`),
+    /// returning a token for reverting it.
+    pub fn perform(&mut self, m: Move) -> Undo {
+        let to = m.to();
+        let team = self.current_team();
+        if let Some(from) = m.from() {
+            self.board.set(from, Field::EMPTY);
         }
-        self.fish[team.index()] += self.board[to].place(team);
+        let fish = self.board.place(to, team);
+        let undo = Undo::new(m, team, fish, self.last_move, self.turn);
+        self.fish[team.index()] += fish;
         self.last_move = Some(m);
         self.turn += 1;
+        undo
+    }
+
+    /// Reverts a move previously performed on this state.
+    pub fn unperform(&mut self, undo: Undo) {
+        let m = undo.performed_move();
+        let team = undo.team();
+        debug_assert!(self.last_move == Some(m), "Can only revert the most recent move");
+        self.board.set(m.to(), Field::with_fish(undo.fish()));
+        if let Some(from) = m.from() {
+            self.board.set(from, Field::with_penguin(team));
+        }
+        self.fish[team.index()] -= undo.fish();
+        self.last_move = undo.last_move();
+        self.turn = undo.turn();
     }
 
     /// Fetches the state after the given move.
@@ -138,6 +218,78 @@ impl State {
     }
 }
 
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist_key());
+    }
+}
+
+/// Formats a coordinate of the compact notation, e.g. `3,5`.
+fn format_notation_coords(c: Vec2<Doubled>) -> String {
+    format!("{},{}", c.x, c.y)
+}
+
+/// Parses a coordinate of the compact notation, e.g. `3,5`.
+fn parse_notation_coords(s: &str) -> Result<Vec2<Doubled>> {
+    let (x, y) = s.split_once(',').ok_or_else(|| Error::Custom(format!("Invalid coordinates: {}", s)))?;
+    Ok(Vec2::new(x.parse()?, y.parse()?))
+}
+
+/// Formats the state in a compact one-line notation consisting of
+/// space-separated parts (similar to the FEN notation in chess):
+///
+/// - The board's rows separated by `/`, using the characters of the board's grid format
+/// - The start team's letter (`R` or `B`)
+/// - The turn
+/// - The fish of team one and team two
+/// - The last move in doubled coordinates (`x,y` for placements, `x,y>x,y` otherwise) or `-`
+///
+/// For example `10031114/10123120/24131122/14341310/01314341/22113142/02132101/41113001 R 0 0 0 -`.
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = self.board.to_string();
+        write!(f, "{} {} {} {} {} ", board.lines().collect::<Vec<_>>().join("/"), self.start_team.letter(), self.turn, self.fish[0], self.fish[1])?;
+        match self.last_move {
+            Some(m) => match m.from() {
+                Some(from) => write!(f, "{}>{}", format_notation_coords(from), format_notation_coords(m.to())),
+                None => write!(f, "{}", format_notation_coords(m.to())),
+            },
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl FromStr for State {
+    type Err = Error;
+
+    /// Parses a state from the compact notation produced by `Display`.
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        let [board, start_team, turn, fish_one, fish_two, last_move] = parts[..] else {
+            return Err(Error::Custom(format!("Expected 6 parts in state notation, got {}: {}", parts.len(), s)));
+        };
+        let mut letters = start_team.chars();
+        let start_team = match (letters.next().and_then(Team::with_letter), letters.next()) {
+            (Some(team), None) => team,
+            _ => return Err(Error::UnknownVariant(format!("Unknown team letter {}", start_team))),
+        };
+        let last_move = match last_move {
+            "-" => None,
+            m => Some(match m.split_once('>') {
+                Some((from, to)) => Move::between(parse_notation_coords(from)?, parse_notation_coords(to)?),
+                None => Move::placing(parse_notation_coords(m)?),
+            }),
+        };
+        Ok(State {
+            board: board.parse()?,
+            turn: turn.parse()?,
+            fish: [fish_one.parse()?, fish_two.parse()?],
+            last_move,
+            start_team,
+        })
+    }
+}
+
 impl TryFrom<&Element> for State {
     type Error = Error;
 
@@ -156,13 +308,28 @@ impl TryFrom<&Element> for State {
     }
 }
 
+impl From<State> for Element {
+    fn from(state: State) -> Self {
+        Element::new("state")
+            .attribute("class", "state")
+            .attribute("turn", state.turn)
+            .child(Element::new("startTeam").content(&state.start_team.to_string()))
+            .child(state.board)
+            .option_child(state.last_move.map(|m| m.element("lastMove")))
+            .child(Element::new("fishes")
+                .childs(state.fish.map(|f| Element::new("int").content(&f.to_string()).build())))
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
     use indoc::indoc;
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
 
-    use crate::{util::Element, game::{Board, Team, State, Move, Vec2, Doubled}};
+    use crate::{util::Element, game::{Board, Team, State, Move, Vec2, Doubled, Field, RuleViolation, BOARD_FIELDS, MAX_FISH}};
 
     #[test]
     fn test_from_xml() {
@@ -268,6 +435,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_to_xml() {
+        let state = State {
+            board: Board::EMPTY,
+            turn: 1,
+            fish: [1, 0],
+            last_move: Some(Move::placing(Vec2::<Doubled>::new(13, 5))),
+            start_team: Team::One,
+        };
+        assert_eq!(Element::from(state), Element::from_str(&format!(
+            r#"<state class="state" turn="1"><startTeam>ONE</startTeam>{}<lastMove><to x="13" y="5"/></lastMove><fishes><int>1</int><int>0</int></fishes></state>"#,
+            Element::from(Board::EMPTY),
+        )).unwrap());
+    }
+
+    #[test]
+    fn test_xml_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut state = State::new(Board::new([(); BOARD_FIELDS].map(|_| Field::with_fish(rng.gen_range(0..=MAX_FISH)))), 0, [0, 0], None, Team::Two);
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            state.perform(m);
+            assert_eq!(State::try_from(&Element::from(state)).unwrap(), state);
+        }
+    }
+
     #[test]
     fn test_possible_moves() {
         let board = indoc! {r#"
@@ -294,4 +486,149 @@ mod tests {
             Move::between(Vec2::<Doubled>::new(3, 7), Vec2::<Doubled>::new(1, 7)),
         ]);
     }
+
+    #[test]
+    fn test_perform_unperform_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let mut state = State {
+                board: Board::new([(); BOARD_FIELDS].map(|_| Field::with_fish(rng.gen_range(0..=MAX_FISH)))),
+                turn: 0,
+                fish: [0, 0],
+                last_move: None,
+                start_team: if rng.gen() { Team::One } else { Team::Two },
+            };
+            while !state.is_over() {
+                let moves = state.possible_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                for &m in &moves {
+                    let before = state;
+                    let undo = state.perform(m);
+                    state.unperform(undo);
+                    assert_eq!(state, before, "Performing and reverting {} changed the state", m);
+                }
+                state.perform(*moves.choose(&mut rng).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zobrist_key_incremental() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut state = State {
+            board: Board::new([(); BOARD_FIELDS].map(|_| Field::with_fish(rng.gen_range(1..=MAX_FISH)))),
+            turn: 0,
+            fish: [0, 0],
+            last_move: None,
+            start_team: Team::One,
+        };
+        let mut keys = vec![state.zobrist_key()];
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            state.perform(m);
+            let fresh = State { board: Board::new(state.board.fields().map(|(_, f)| f).collect::<Vec<_>>().try_into().unwrap()), ..state };
+            assert_eq!(state.zobrist_key(), fresh.zobrist_key());
+            assert!(!keys.contains(&state.zobrist_key()), "Zobrist key collision after {}", m);
+            keys.push(state.zobrist_key());
+        }
+    }
+
+    #[test]
+    fn test_validate_matches_possible_moves() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..5 {
+            let mut state = State::new(Board::generate(&mut rng), 0, [0, 0], None, Team::One);
+            while let Some(&m) = state.possible_moves().choose(&mut rng) {
+                let moves = state.possible_moves();
+                let sources: Vec<_> = if state.penguins_placed() {
+                    state.current_pieces().map(|(c, _)| Some(c)).collect()
+                } else {
+                    vec![None]
+                };
+                for from in sources {
+                    for (to, _) in state.board().fields() {
+                        let candidate = Move::new(from, to);
+                        assert_eq!(state.validate(candidate).is_ok(), moves.contains(&candidate), "Validation of {} disagrees", candidate);
+                    }
+                }
+                state.try_perform(m).unwrap();
+            }
+            assert_eq!(state.validate(Move::placing(Vec2::<Doubled>::new(0, 0))), Err(RuleViolation::GameOver));
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let board = indoc! {r#"
+            R1010000
+            04000000
+            B0000000
+            00000000
+            R000000B
+            00000000
+            R000000B
+            00000011
+        "#}.parse::<Board>().unwrap();
+        let mut state = State::new(board, 0, [0, 0], None, Team::One);
+        let place = |x, y| Move::placing(Vec2::<Doubled>::new(x, y));
+        assert_eq!(state.validate(place(2, 0)), Ok(()));
+        assert_eq!(state.validate(place(3, 1)), Err(RuleViolation::PlacementNotOnSingleFish(Vec2::new(3, 1))));
+        assert_eq!(state.validate(place(0, 2)), Err(RuleViolation::DestinationOccupied(Vec2::new(0, 2))));
+        assert_eq!(state.validate(place(16, 0)), Err(RuleViolation::OutOfBounds(Vec2::new(16, 0))));
+        assert_eq!(state.validate(Move::between(Vec2::<Doubled>::new(0, 0), Vec2::<Doubled>::new(2, 0))), Err(RuleViolation::MovingDuringPlacementPhase));
+
+        state.perform(place(2, 0));
+        state.perform(place(13, 7));
+        let slide = |fx, fy, tx, ty| Move::between(Vec2::<Doubled>::new(fx, fy), Vec2::<Doubled>::new(tx, ty));
+        assert_eq!(state.validate(place(15, 7)), Err(RuleViolation::PlacingAfterPlacementPhase));
+        assert_eq!(state.validate(slide(0, 0, 3, 1)), Err(RuleViolation::NotStraight));
+        assert_eq!(state.validate(slide(0, 0, 0, 0)), Err(RuleViolation::NoMovement));
+        assert_eq!(state.validate(slide(0, 2, 1, 1)), Err(RuleViolation::NotOwnPenguin(Vec2::new(0, 2))));
+        assert_eq!(state.validate(slide(0, 0, 2, 0)), Err(RuleViolation::DestinationOccupied(Vec2::new(2, 0))));
+        assert_eq!(state.validate(slide(0, 0, 6, 0)), Err(RuleViolation::PathBlocked(Vec2::new(2, 0))));
+        assert_eq!(state.validate(slide(2, 0, 6, 0)), Err(RuleViolation::PathBlocked(Vec2::new(4, 0))));
+        assert_eq!(state.validate(slide(0, 0, 1, 1)), Err(RuleViolation::PathBlocked(Vec2::new(1, 1))));
+        assert_eq!(state.validate(slide(2, 0, 3, 1)), Ok(()));
+        assert!(state.try_perform(slide(0, 0, 2, 2)).is_err());
+        assert_eq!(state.turn(), 2);
+    }
+
+    #[test]
+    fn test_validate_parity() {
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        assert_eq!(state.validate(Move::placing(Vec2::<Doubled>::new(0, 0))), Ok(()));
+        assert_eq!(state.validate(Move::placing(Vec2::<Doubled>::new(1, 0))), Err(RuleViolation::OutOfBounds(Vec2::new(1, 0))));
+        assert_eq!(state.validate(Move::placing(Vec2::<Doubled>::new(0, 1))), Err(RuleViolation::OutOfBounds(Vec2::new(0, 1))));
+        assert_eq!(state.validate(Move::between(Vec2::<Doubled>::new(2, 1), Vec2::<Doubled>::new(3, 1))), Err(RuleViolation::OutOfBounds(Vec2::new(2, 1))));
+    }
+
+    #[test]
+    fn test_notation() {
+        let notation = "10031114/10123120/24131122/14341310/01314341/22113142/02132101/41113001 B 3 1 2 5,5>7,5";
+        let state: State = notation.parse().unwrap();
+        assert_eq!(state.start_team(), Team::Two);
+        assert_eq!(state.turn(), 3);
+        assert_eq!((state.fish(Team::One), state.fish(Team::Two)), (1, 2));
+        assert_eq!(state.last_move(), Some(Move::between(Vec2::<Doubled>::new(5, 5), Vec2::<Doubled>::new(7, 5))));
+        assert_eq!(state.board()[Vec2::<Doubled>::new(0, 2)], Field::with_fish(2));
+        assert_eq!(state.to_string(), notation);
+
+        for invalid in ["", "10031114 R 0 0 0 -", "10031114/10123120/24131122/14341310/01314341/22113142/02132101/41113001 X 0 0 0 -", "10031114/10123120/24131122/14341310/01314341/22113142/02132101/41113001 R 0 0 0 3;4"] {
+            assert!(invalid.parse::<State>().is_err(), "{} should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn test_notation_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mut state = State::new(Board::generate(&mut rng), 0, [0, 0], None, Team::Two);
+        loop {
+            assert_eq!(state.to_string().parse::<State>().unwrap(), state);
+            match state.possible_moves().choose(&mut rng) {
+                Some(&m) => { state.perform(m); },
+                None => break,
+            }
+        }
+    }
 }
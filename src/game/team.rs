@@ -21,7 +21,7 @@ impl Team {
     }
 
     /// The team's index.
-    pub fn index(self) -> usize {
+    pub const fn index(self) -> usize {
         match self {
             Self::One => 0,
             Self::Two => 1,
@@ -0,0 +1,34 @@
+use super::{Move, Team};
+
+/// The information needed to revert a performed move,
+/// as returned by `State::perform`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Undo {
+    m: Move,
+    team: Team,
+    fish: usize,
+    last_move: Option<Move>,
+    turn: usize,
+}
+
+impl Undo {
+    /// Creates a new undo token.
+    pub fn new(m: Move, team: Team, fish: usize, last_move: Option<Move>, turn: usize) -> Self {
+        Self { m, team, fish, last_move, turn }
+    }
+
+    /// The performed move.
+    pub fn performed_move(self) -> Move { self.m }
+
+    /// The team that performed the move.
+    pub fn team(self) -> Team { self.team }
+
+    /// The fish collected by the move, i.e. removed from the target field.
+    pub fn fish(self) -> usize { self.fish }
+
+    /// The most recent move before the move was performed.
+    pub fn last_move(self) -> Option<Move> { self.last_move }
+
+    /// The turn before the move was performed.
+    pub fn turn(self) -> usize { self.turn }
+}
@@ -100,6 +100,18 @@ impl Vec2<Doubled> {
         self.x.abs() == self.y.abs() || (self.x % 2 == 0 && self.y == 0)
     }
 
+    /// Splits a straight, non-zero vector into a unit vector from
+    /// `DIRECTIONS` and the number of steps in that direction.
+    pub fn direction_and_distance(self) -> Option<(Self, i32)> {
+        if self == Self::ZERO || !self.straight() {
+            None
+        } else if self.y == 0 {
+            Some((Self::new(self.x.signum() * 2, 0), self.x.abs() / 2))
+        } else {
+            Some((Self::new(self.x.signum(), self.y.signum()), self.y.abs()))
+        }
+    }
+
     /// Fetches the 6 hex neighbors.
     pub fn hex_neighbors(self) -> [Self; 6] {
         Self::DIRECTIONS.map(|v| self + v)
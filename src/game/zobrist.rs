@@ -0,0 +1,43 @@
+use super::{BOARD_FIELDS, MAX_FISH, TEAMS};
+
+// See https://www.chessprogramming.org/Zobrist_Hashing
+
+/// A compile-time pseudo-random number generator (SplitMix64)
+/// for deterministically generating the Zobrist keys.
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (state, z ^ (z >> 31))
+}
+
+/// The random keys, one per (field, fish count), (field, team), side to move and placement phase.
+const KEYS: [u64; BOARD_FIELDS * (MAX_FISH + TEAMS) + 2] = {
+    let mut keys = [0; BOARD_FIELDS * (MAX_FISH + TEAMS) + 2];
+    let mut state = 0x50C4A2023;
+    let mut i = 0;
+    while i < keys.len() {
+        let (next_state, key) = splitmix64(state);
+        keys[i] = key;
+        state = next_state;
+        i += 1;
+    }
+    keys
+};
+
+/// The Zobrist key for the given number of fish (at least 1) on the field with the given index.
+pub(crate) const fn fish_key(index: usize, fish: usize) -> u64 {
+    KEYS[index * (MAX_FISH + TEAMS) + fish - 1]
+}
+
+/// The Zobrist key for a penguin of the team with the given index on the field with the given index.
+pub(crate) const fn penguin_key(index: usize, team: usize) -> u64 {
+    KEYS[index * (MAX_FISH + TEAMS) + MAX_FISH + team]
+}
+
+/// The Zobrist key toggled when the second team is to move.
+pub(crate) const SIDE_KEY: u64 = KEYS[BOARD_FIELDS * (MAX_FISH + TEAMS)];
+
+/// The Zobrist key toggled during the placement phase.
+pub(crate) const PLACEMENT_KEY: u64 = KEYS[BOARD_FIELDS * (MAX_FISH + TEAMS) + 1];
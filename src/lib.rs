@@ -1,4 +1,11 @@
+pub mod arena;
 pub mod client;
 pub mod protocol;
+pub mod replay;
+pub mod search;
+pub mod server;
 pub mod game;
+pub mod logic;
 pub mod util;
+#[cfg(feature = "tui")]
+pub mod viewer;
@@ -1,14 +1,14 @@
 use log::{info, debug};
 use rand::seq::SliceRandom;
 
-use socha_client_2023::{client::GameClientDelegate, game::{Move, Team, State}};
+use crate::{client::{GameClientDelegate, TimeBudget}, game::{Move, Team, State}};
 
 /// An empty game logic structure that implements the client delegate trait
 /// and thus is responsible e.g. for picking a move when requested.
 pub struct OwnLogic;
 
 impl GameClientDelegate for OwnLogic {
-    fn request_move(&mut self, state: &State, _my_team: Team) -> Move {
+    fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
         info!("Requested move");
         let chosen_move = *state.possible_moves()
             .choose(&mut rand::thread_rng())
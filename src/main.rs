@@ -1,12 +1,13 @@
-mod logic;
-
+use std::path::PathBuf;
+use std::process;
 use std::str::FromStr;
-use clap::Parser;
+use std::time::{Duration, Instant};
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand};
 use simplelog::{SimpleLogger, Config};
 use log::LevelFilter;
-use socha_client_2023::client::{GameClient, DebugMode};
-
-use logic::OwnLogic;
+use socha_client_2023::client::{GameClient, DebugMode, ReconnectPolicy, TimeBudget};
+use socha_client_2023::game::{State, perft, perft_divide, PERFT_POSITIONS};
+use socha_client_2023::logic::OwnLogic;
 
 /// Software Challenge 2023 client.
 #[derive(Parser, Debug)]
@@ -29,11 +30,115 @@ struct Args {
     /// Prints outgoing XML messages to the console for debugging.
     #[clap(short = 'D', long)]
     debug_writer: bool,
+    /// The time (in milliseconds) subtracted from the server's timeout when computing a move.
+    #[clap(long, default_value_t = TimeBudget::DEFAULT_SAFETY_MARGIN.as_millis() as u64)]
+    safety_margin: u64,
+    /// How often to try reconnecting in a row if the connection drops.
+    #[clap(long, default_value_t = 0)]
+    reconnect: u32,
+    /// The number of games to play (joining again after each result).
+    #[clap(short, long, default_value_t = 1)]
+    games: usize,
+    /// The number of clients playing the games concurrently (without a reservation or debugging).
+    #[clap(long, default_value_t = 1)]
+    sessions: usize,
+    /// A directory to record a replay of each game to.
+    #[clap(long)]
+    replays: Option<PathBuf>,
+    /// Compresses the recorded replays using gzip.
+    #[clap(long, requires = "replays")]
+    compress_replays: bool,
+    /// Records the replays in the official format. By default, they also contain the own moves, which the official server's replays lack.
+    #[clap(long, requires = "replays")]
+    official_replays: bool,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Counts the leaf nodes of the game tree in the reference positions
+    /// to verify the move generator and measure its speed.
+    Perft {
+        /// The depth to search to (defaults to the deepest known node count).
+        #[clap(short, long)]
+        depth: Option<usize>,
+        /// Only runs the reference position with the given name.
+        #[clap(long, conflicts_with = "state")]
+        position: Option<String>,
+        /// Runs the given state in compact notation instead of the reference positions.
+        #[clap(long)]
+        state: Option<String>,
+        /// Prints the node counts below each move.
+        #[clap(long)]
+        divide: bool,
+    },
+}
+
+/// The depth to run perft to in positions without known node counts.
+const DEFAULT_PERFT_DEPTH: usize = 4;
+
+/// Runs perft on the reference positions (or the given state), exiting
+/// with an error code if a node count differs from the known one.
+fn run_perft(depth: Option<usize>, name: Option<&str>, notation: Option<&str>, divide: bool) {
+    if let Some(name) = name.filter(|&n| PERFT_POSITIONS.iter().all(|p| p.name() != n)) {
+        eprintln!("Unknown position {}", name);
+        process::exit(2);
+    }
+    let positions: Vec<(&str, State, &[u64])> = match notation {
+        Some(notation) => match notation.parse() {
+            Ok(state) => vec![("custom", state, &[])],
+            Err(e) => {
+                eprintln!("Invalid state notation: {:?}", e);
+                process::exit(2);
+            },
+        },
+        None => PERFT_POSITIONS.iter()
+            .filter(|p| name.is_none_or(|n| n == p.name()))
+            .map(|p| (p.name(), p.state(), p.nodes()))
+            .collect(),
+    };
+    let mut failed = false;
+    for (name, mut state, known_nodes) in positions {
+        let max_depth = depth.unwrap_or(if known_nodes.is_empty() { DEFAULT_PERFT_DEPTH } else { known_nodes.len() });
+        println!("Position {} (current team: {})", name, state.current_team());
+        println!("  {}", state);
+        for depth in 1..=max_depth {
+            let start = Instant::now();
+            let nodes = perft(&mut state, depth);
+            let elapsed = start.elapsed();
+            let verdict = match known_nodes.get(depth - 1) {
+                Some(&n) if n == nodes => "ok".to_owned(),
+                Some(&n) => { failed = true; format!("expected {}", n) },
+                None => "unknown".to_owned(),
+            };
+            println!("  depth {}: {} nodes in {:?} ({:.0} nodes/s, {})", depth, nodes, elapsed, nodes as f64 / elapsed.as_secs_f64(), verdict);
+        }
+        if divide {
+            for (m, nodes) in perft_divide(&mut state, max_depth) {
+                println!("  {}: {}", m.notation(), nodes);
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
 }
 
 fn main() {
     // Parse command line arguments
     let args = Args::parse();
+    if args.sessions > 1 && (args.reservation.is_some() || args.debug_reader || args.debug_writer) {
+        Args::command().error(ErrorKind::ArgumentConflict, "Concurrent sessions cannot use a reservation or debug the XML messages").exit();
+    }
+    if args.games > 1 && args.reservation.is_some() {
+        Args::command().error(ErrorKind::ArgumentConflict, "A reservation is only valid for a single game").exit();
+    }
+
+    if let Some(Command::Perft { depth, position, state, divide }) = &args.command {
+        run_perft(*depth, position.as_deref(), state.as_deref(), *divide);
+        return;
+    }
     
     // Set up logging
     SimpleLogger::init(LevelFilter::from_str(&args.level).expect("Invalid log level."), Config::default()).expect("Could not initialize logger.");
@@ -44,6 +149,27 @@ fn main() {
         debug_writer: args.debug_writer,
     };
 
-    let client = GameClient::new(OwnLogic, debug_mode, args.reservation);
-    let _result = client.connect(&args.host, args.port).expect("Error while running client.");
+    let new_client = |debug_mode, reservation| {
+        let client = GameClient::new(OwnLogic, debug_mode, reservation)
+            .with_fallback(true)
+            .with_safety_margin(Duration::from_millis(args.safety_margin))
+            .with_reconnect_policy(ReconnectPolicy::new(args.reconnect))
+            .with_replay_compression(args.compress_replays)
+            .with_replay_moves(!args.official_replays);
+        match &args.replays {
+            Some(directory) => client.with_replay_directory(directory.clone()),
+            None => client,
+        }
+    };
+    if args.games == 1 && args.sessions == 1 {
+        let _result = new_client(debug_mode, args.reservation).connect(&args.host, args.port).expect("Error while running client.");
+    } else if args.sessions == 1 {
+        let statistics = new_client(debug_mode, args.reservation).connect_games(&args.host, args.port, args.games).expect("Error while running client.");
+        println!("After {} games: {}", statistics.games(), statistics);
+    } else {
+        let statistics = GameClient::connect_concurrently(&args.host, args.port, args.sessions, args.games, |_| {
+            new_client(DebugMode { debug_reader: false, debug_writer: false }, None)
+        });
+        println!("After {} games: {}", statistics.games(), statistics);
+    }
 }
@@ -9,6 +9,11 @@ pub enum Event {
     Joined { room_id: String },
     /// Notifies the client that they left a room.
     Left { room_id: String },
+    /// Notifies the client that they are observing a room.
+    Observed { room_id: String },
+    /// Notifies an administrator that a room was prepared
+    /// with the given reservation codes (one per slot).
+    Prepared { room_id: String, reservations: Vec<String> },
     /// A message in a room.
     Room { room_id: String, payload: EventPayload },
 }
@@ -20,6 +25,12 @@ impl TryFrom<&Element> for Event {
         match elem.name() {
             "joined" => Ok(Self::Joined { room_id: elem.attribute("roomId")?.to_owned() }),
             "left" => Ok(Self::Left { room_id: elem.attribute("roomId")?.to_owned() }),
+            "observed" => Ok(Self::Observed { room_id: elem.attribute("roomId")?.to_owned() }),
+            "prepared" => Ok(Self::Prepared {
+                room_id: elem.attribute("roomId")?.to_owned(),
+                reservations: elem.childs_by_name("reservation").map(|r| r.content().to_owned()).collect(),
+            }),
+            "errorpacket" => Err(Error::ServerError(elem.attribute("message")?.to_owned())),
             "room" => Ok(Self::Room {
                 room_id: elem.attribute("roomId")?.to_owned(),
                 payload: elem.child_by_name("data")?.try_into()?,
@@ -28,3 +39,18 @@ impl TryFrom<&Element> for Event {
         }
     }
 }
+
+impl From<Event> for Element {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Joined { room_id } => Element::new("joined").attribute("roomId", room_id).build(),
+            Event::Left { room_id } => Element::new("left").attribute("roomId", room_id).build(),
+            Event::Observed { room_id } => Element::new("observed").attribute("roomId", room_id).build(),
+            Event::Prepared { room_id, reservations } => Element::new("prepared")
+                .attribute("roomId", room_id)
+                .childs(reservations.iter().map(|r| Element::new("reservation").content(r).build()))
+                .build(),
+            Event::Room { room_id, payload } => Element::new("room").attribute("roomId", room_id).child(payload).build(),
+        }
+    }
+}
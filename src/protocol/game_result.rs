@@ -47,6 +47,20 @@ impl TryFrom<&Element> for GameResult {
     }
 }
 
+impl From<GameResult> for Element {
+    fn from(result: GameResult) -> Self {
+        Element::new("data")
+            .attribute("class", "result")
+            .child(result.definition)
+            .child(Element::new("scores").childs(result.scores.into_iter().map(|(player, score)| Element::new("entry")
+                .child(player)
+                .child(score)
+                .build())))
+            .option_child(result.winner.map(|w| Element::new("winner").attribute("team", w.team())))
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -99,4 +113,17 @@ mod tests {
             Some(Player::new(None, Team::One))
         ));
     }
+
+    #[test]
+    fn test_xml_roundtrip() {
+        let result = GameResult::new(
+            ScoreDefinition::new([ScoreDefinitionFragment::new("Siegpunkte", ScoreAggregation::Sum, true)]),
+            hashmap![
+                Player::new(Some("rad"), Team::One) => Score::new(ScoreCause::Regular, "", [2]),
+                Player::new(Some("blues"), Team::Two) => Score::new(ScoreCause::Left, "Player left", [0])
+            ],
+            Some(Player::new(None, Team::One))
+        );
+        assert_eq!(GameResult::try_from(&Element::from(result.clone())).unwrap(), result);
+    }
 }
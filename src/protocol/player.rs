@@ -13,7 +13,7 @@ impl Player {
     }
 
     #[inline]
-    pub fn name(&self) -> Option<&str> { self.name.as_ref().map(|n| n.as_str()) }
+    pub fn name(&self) -> Option<&str> { self.name.as_deref() }
 
     #[inline]
     pub fn team(&self) -> Team { self.team }
@@ -30,6 +30,16 @@ impl TryFrom<&Element> for Player {
     }
 }
 
+impl From<Player> for Element {
+    fn from(player: Player) -> Self {
+        let builder = Element::new("player");
+        match player.name {
+            Some(name) => builder.attribute("name", name),
+            None => builder,
+        }.attribute("team", player.team).build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -1,6 +1,6 @@
-use crate::util::Element;
+use crate::util::{Element, Error, Result};
 
-use super::RequestPayload;
+use super::{RequestPayload, SlotDescriptor};
 
 const GAME_TYPE: &str = "swc_2023_penguins";
 
@@ -14,6 +14,21 @@ pub enum Request {
     /// Joins a reserved place in a planned match with
     /// a reservation code.
     JoinPrepared { reservation_code: String },
+    /// Authenticates the client as an administrator, which
+    /// is required e.g. for observing rooms.
+    Authenticate { passphrase: String },
+    /// Observes the room with the given id as a spectator.
+    Observe { room_id: String },
+    /// Creates a room with reserved slots for the given players,
+    /// optionally starting paused (requires authentication).
+    Prepare { slots: Vec<SlotDescriptor>, pause: bool },
+    /// Pauses or unpauses the game in the given room (requires authentication).
+    Pause { room_id: String, pause: bool },
+    /// Advances the paused game in the given room by
+    /// a single move (requires authentication).
+    Step { room_id: String },
+    /// Closes the given room (requires authentication).
+    Cancel { room_id: String },
     /// A message in a room.
     Room { room_id: String, payload: RequestPayload },
 }
@@ -24,7 +39,87 @@ impl From<Request> for Element {
             Request::Join => Element::new("join").attribute("gameType", GAME_TYPE).build(),
             Request::JoinRoom { room_id } => Element::new("joinRoom").attribute("roomId", room_id).build(),
             Request::JoinPrepared { reservation_code } => Element::new("joinPrepared").attribute("reservationCode", reservation_code).build(),
+            Request::Authenticate { passphrase } => Element::new("authenticate").attribute("passphrase", passphrase).build(),
+            Request::Observe { room_id } => Element::new("observe").attribute("roomId", room_id).build(),
+            Request::Prepare { slots, pause } => Element::new("prepare").attribute("gameType", GAME_TYPE).attribute("pause", pause).childs(slots.into_iter().map(Element::from)).build(),
+            Request::Pause { room_id, pause } => Element::new("pause").attribute("roomId", room_id).attribute("pause", pause).build(),
+            Request::Step { room_id } => Element::new("step").attribute("roomId", room_id).build(),
+            Request::Cancel { room_id } => Element::new("cancel").attribute("roomId", room_id).build(),
             Request::Room { room_id, payload } => Element::new("room").attribute("roomId", room_id).child(payload).build(),
         }
     }
 }
+
+impl TryFrom<&Element> for Request {
+    type Error = Error;
+
+    fn try_from(elem: &Element) -> Result<Self> {
+        match elem.name() {
+            "join" => Ok(Self::Join),
+            "joinRoom" => Ok(Self::JoinRoom { room_id: elem.attribute("roomId")?.to_owned() }),
+            "joinPrepared" => Ok(Self::JoinPrepared { reservation_code: elem.attribute("reservationCode")?.to_owned() }),
+            "authenticate" => Ok(Self::Authenticate { passphrase: elem.attribute("passphrase")?.to_owned() }),
+            "observe" => Ok(Self::Observe { room_id: elem.attribute("roomId")?.to_owned() }),
+            "prepare" => Ok(Self::Prepare {
+                slots: elem.childs_by_name("slot").map(SlotDescriptor::try_from).collect::<Result<_>>()?,
+                pause: elem.attribute("pause")?.parse()?,
+            }),
+            "pause" => Ok(Self::Pause { room_id: elem.attribute("roomId")?.to_owned(), pause: elem.attribute("pause")?.parse()? }),
+            "step" => Ok(Self::Step { room_id: elem.attribute("roomId")?.to_owned() }),
+            "cancel" => Ok(Self::Cancel { room_id: elem.attribute("roomId")?.to_owned() }),
+            "room" => Ok(Self::Room {
+                room_id: elem.attribute("roomId")?.to_owned(),
+                payload: elem.child_by_name("data")?.try_into()?,
+            }),
+            _ => Err(Error::UnknownElement(elem.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use indoc::indoc;
+
+    use crate::{util::Element, protocol::{Request, RequestPayload, SlotDescriptor}, game::{Move, Vec2, Doubled}};
+
+    #[test]
+    fn test_from_xml() {
+        let request = Request::try_from(&Element::from_str(indoc! {r#"
+            <room roomId="abc">
+                <data class="move">
+                    <to x="3" y="1"/>
+                </data>
+            </room>
+        "#}).unwrap()).unwrap();
+        match request {
+            Request::Room { room_id, payload: RequestPayload::Move(m) } => {
+                assert_eq!(room_id, "abc");
+                assert_eq!(m, Move::placing(Vec2::<Doubled>::new(3, 1)));
+            },
+            _ => panic!("Unexpected request {:?}", request),
+        }
+    }
+
+    #[test]
+    fn test_prepare_xml() {
+        let request: Element = Request::Prepare {
+            slots: vec![SlotDescriptor::new("Alice"), SlotDescriptor::new("Bob").with_can_timeout(false)],
+            pause: true,
+        }.into();
+        assert_eq!(request, Element::from_str(indoc! {r#"
+            <prepare gameType="swc_2023_penguins" pause="true">
+                <slot displayName="Alice" canTimeout="true" reserved="true"/>
+                <slot displayName="Bob" canTimeout="false" reserved="true"/>
+            </prepare>
+        "#}).unwrap());
+        match Request::try_from(&request).unwrap() {
+            Request::Prepare { slots, pause } => {
+                assert_eq!(slots[1], SlotDescriptor::new("Bob").with_can_timeout(false));
+                assert!(pause);
+            },
+            request => panic!("Unexpected request {:?}", request),
+        }
+    }
+}
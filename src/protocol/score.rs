@@ -37,6 +37,16 @@ impl TryFrom<&Element> for Score {
     }
 }
 
+impl From<Score> for Element {
+    fn from(score: Score) -> Self {
+        Element::new("score")
+            .attribute("cause", score.cause)
+            .attribute("reason", score.reason)
+            .childs(score.parts.into_iter().map(|p| Element::new("part").content(&p.to_string()).build()))
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
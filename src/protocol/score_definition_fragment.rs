@@ -35,3 +35,13 @@ impl TryFrom<&Element> for ScoreDefinitionFragment {
         })
     }
 }
+
+impl From<ScoreDefinitionFragment> for Element {
+    fn from(fragment: ScoreDefinitionFragment) -> Self {
+        Element::new("fragment")
+            .attribute("name", fragment.name)
+            .child(Element::new("aggregation").content(&fragment.aggregation.to_string()))
+            .child(Element::new("relevantForRanking").content(&fragment.relevant_for_ranking.to_string()))
+            .build()
+    }
+}
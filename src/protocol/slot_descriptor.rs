@@ -0,0 +1,59 @@
+use crate::util::{Element, Error, Result};
+
+/// Describes a player slot of a game prepared by an administrator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SlotDescriptor {
+    display_name: String,
+    can_timeout: bool,
+    reserved: bool,
+}
+
+impl SlotDescriptor {
+    /// Creates a reserved slot with the given display name whose
+    /// player is subject to the time limits.
+    pub fn new(display_name: &str) -> Self {
+        Self { display_name: display_name.to_owned(), can_timeout: true, reserved: true }
+    }
+
+    /// Sets whether the player may be timed out (defaults to true).
+    pub fn with_can_timeout(self, can_timeout: bool) -> Self {
+        Self { can_timeout, ..self }
+    }
+
+    /// Sets whether the slot can only be joined with its reservation
+    /// code (defaults to true).
+    pub fn with_reserved(self, reserved: bool) -> Self {
+        Self { reserved, ..self }
+    }
+
+    #[inline]
+    pub fn display_name(&self) -> &str { &self.display_name }
+
+    #[inline]
+    pub fn can_timeout(&self) -> bool { self.can_timeout }
+
+    #[inline]
+    pub fn reserved(&self) -> bool { self.reserved }
+}
+
+impl TryFrom<&Element> for SlotDescriptor {
+    type Error = Error;
+
+    fn try_from(elem: &Element) -> Result<Self> {
+        Ok(SlotDescriptor {
+            display_name: elem.attribute("displayName")?.to_owned(),
+            can_timeout: elem.attribute("canTimeout")?.parse()?,
+            reserved: elem.attribute("reserved")?.parse()?,
+        })
+    }
+}
+
+impl From<SlotDescriptor> for Element {
+    fn from(slot: SlotDescriptor) -> Self {
+        Element::new("slot")
+            .attribute("displayName", slot.display_name)
+            .attribute("canTimeout", slot.can_timeout)
+            .attribute("reserved", slot.reserved)
+            .build()
+    }
+}
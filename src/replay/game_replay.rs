@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use flate2::bufread::GzDecoder;
+use log::{debug, warn};
+use quick_xml::Reader;
+use crate::game::{GameHistory, State, Move};
+use crate::protocol::{Event, EventPayload, GameResult, Request, RequestPayload};
+use crate::util::{Element, Error, Result};
+
+use super::ReplayCursor;
+
+/// The magic bytes at the start of gzip-compressed files.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// A recorded game, as written by the official server
+/// (or the `ReplayRecorder`), i.e. a `<protocol>` element
+/// containing the room messages of the game. Replays
+/// that are missing states are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct GameReplay {
+    room_id: Option<String>,
+    history: GameHistory,
+    result: Option<GameResult>,
+}
+
+impl GameReplay {
+    /// Loads the replay from the given file, which may be gzip-compressed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Reads the replay from the given stream, which may be gzip-compressed.
+    pub fn read_from(read: impl Read) -> Result<Self> {
+        let mut read = BufReader::new(read);
+        let element = if read.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Element::read_from(&mut Reader::from_reader(BufReader::new(GzDecoder::new(read))))?
+        } else {
+            Element::read_from(&mut Reader::from_reader(read))?
+        };
+        Self::try_from(&element)
+    }
+
+    /// Fetches the id of the room the game was played in.
+    pub fn room_id(&self) -> Option<&str> { self.room_id.as_deref() }
+
+    /// Fetches the states and moves of the game.
+    pub fn history(&self) -> &GameHistory { &self.history }
+
+    /// Fetches the states of the game in chronological order.
+    pub fn states(&self) -> &[State] { self.history.states() }
+
+    /// Fetches the moves of the game in chronological order.
+    pub fn moves(&self) -> &[Move] { self.history.moves() }
+
+    /// Fetches the game's result, if the replay contains it.
+    pub fn result(&self) -> Option<&GameResult> { self.result.as_ref() }
+
+    /// Whether the replay contains no states.
+    pub fn is_empty(&self) -> bool { self.history.is_empty() }
+
+    /// Creates a cursor for stepping through the
+    /// states, starting at the initial state.
+    pub fn cursor(&self) -> ReplayCursor<'_> { ReplayCursor::new(&self.history) }
+}
+
+impl GameReplay {
+    /// Records the given memento, failing if the states before it are missing.
+    /// Mementos that are not newer than the most recent one are skipped.
+    fn push_memento(&mut self, state: State) -> Result<()> {
+        if let Some(previous) = self.history.current_state() {
+            if state.turn() <= previous.turn() {
+                if state != *previous {
+                    warn!("Skipping memento of turn {} after turn {} in replay", state.turn(), previous.turn());
+                }
+                return Ok(());
+            }
+            if state.turn() != previous.turn() + 1 || state.last_move().is_none() {
+                return Err(Error::InvalidState(format!("Replay is missing states between turn {} and {}", previous.turn(), state.turn())));
+            }
+        }
+        self.history.push(state);
+        Ok(())
+    }
+}
+
+impl TryFrom<&Element> for GameReplay {
+    type Error = Error;
+
+    fn try_from(elem: &Element) -> Result<Self> {
+        if elem.name() != "protocol" {
+            return Err(Error::UnknownElement(elem.clone()));
+        }
+
+        let mut replay = Self::default();
+        for message in elem.childs_by_name("room") {
+            match Event::try_from(message) {
+                Ok(Event::Room { room_id, payload }) => {
+                    replay.room_id.get_or_insert(room_id);
+                    match payload {
+                        EventPayload::Memento(state) => replay.push_memento(state)?,
+                        EventPayload::GameResult(result) => replay.result = Some(result),
+                        payload => debug!("Skipping {} in replay", payload),
+                    }
+                },
+                // The moves are derived from the mementos instead
+                Err(_) if matches!(Request::try_from(message), Ok(Request::Room { payload: RequestPayload::Move(_), .. })) => (),
+                Ok(event) => debug!("Skipping {:?} in replay", event),
+                Err(e) => warn!("Could not parse message in replay: {:?}", e),
+            }
+        }
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{game::{Doubled, Move, Team, Vec2}, protocol::{Event, EventPayload}, replay::ReplayRecorder, server::{game_result, LocalServer}, util::{Element, Error}};
+
+    use super::GameReplay;
+
+    #[test]
+    fn test_load_official() {
+        let plain = GameReplay::read_from(&include_bytes!("fixtures/placement.xml")[..]).unwrap();
+        let compressed = GameReplay::read_from(&include_bytes!("fixtures/placement.xml.gz")[..]).unwrap();
+        for replay in [plain, compressed] {
+            assert_eq!(replay.room_id(), Some("5e8b2c40-1f7a-4b9e-9d2a-3c6f0e1a7b21"));
+            assert_eq!(replay.states().len(), 5);
+            assert_eq!(replay.history().moves_with_teams().collect::<Vec<_>>(), vec![
+                (Move::placing(Vec2::<Doubled>::new(0, 0)), Team::One),
+                (Move::placing(Vec2::<Doubled>::new(15, 7)), Team::Two),
+                (Move::placing(Vec2::<Doubled>::new(8, 0)), Team::One),
+                (Move::placing(Vec2::<Doubled>::new(7, 7)), Team::Two),
+            ]);
+            let replayed = replay.moves().iter().fold(replay.states()[0], |s, &m| s.child(m));
+            assert_eq!(Some(&replayed), replay.history().current_state());
+            assert_eq!(replay.result().and_then(|r| r.winner().as_ref()).map(|p| p.team()), Some(Team::One));
+        }
+    }
+
+    #[test]
+    fn test_missing_states() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let mut states = vec![*LocalServer::random(&mut rng).state()];
+        for _ in 0..3 {
+            let state = states.last().unwrap();
+            states.push(state.child(*state.possible_moves().choose(&mut rng).unwrap()));
+        }
+        let protocol = |states: &[_]| Element::new("protocol")
+            .childs(states.iter().map(|&s| Element::from(Event::Room { room_id: "r".to_owned(), payload: EventPayload::Memento(s) })))
+            .build();
+
+        // Repeated and outdated mementos are skipped
+        let replay = GameReplay::try_from(&protocol(&[states[0], states[1], states[1], states[0], states[2]])).unwrap();
+        assert_eq!(replay.states(), &states[..3]);
+
+        let result = GameReplay::try_from(&protocol(&[states[0], states[1], states[3]]));
+        assert!(matches!(result, Err(Error::InvalidState(_))), "Expected invalid state, got {:?}", result);
+    }
+
+    #[test]
+    fn test_load_recorded() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut state = *LocalServer::random(&mut rng).state();
+        let mut states = vec![state];
+        let mut moves = Vec::new();
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            state.perform(m);
+            states.push(state);
+            moves.push(m);
+        }
+        let result = game_result(&state, None);
+
+        for compress in [false, true] {
+            let directory = env::temp_dir().join(format!("socha-replay-load-test-{}-{}", std::process::id(), compress));
+            let mut recorder = ReplayRecorder::create(&directory, "room", compress).unwrap();
+            for (i, s) in states.iter().enumerate() {
+                recorder.record_memento(s).unwrap();
+                if let Some(&m) = moves.get(i).filter(|_| i % 2 == 0) {
+                    recorder.record_move(m).unwrap();
+                }
+            }
+            recorder.record_result(&result).unwrap();
+            recorder.finish().unwrap();
+
+            let path = fs::read_dir(&directory).unwrap().next().unwrap().unwrap().path();
+            let replay = GameReplay::load(&path).unwrap();
+            fs::remove_dir_all(&directory).unwrap();
+
+            assert_eq!(replay.room_id(), Some("room"));
+            assert_eq!(replay.states(), &states[..]);
+            assert_eq!(replay.moves(), &moves[..]);
+            assert_eq!(replay.result(), Some(&result));
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod game_replay;
+mod replay_cursor;
+mod replay_recorder;
+
+pub use game_replay::*;
+pub use replay_cursor::*;
+pub use replay_recorder::*;
@@ -0,0 +1,109 @@
+use crate::game::{GameHistory, State, Move};
+
+/// A position within a replay (or any other game history) that can
+/// be moved forward and backward through the game's states.
+/// Iterating the cursor steps forward.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayCursor<'a> {
+    history: &'a GameHistory,
+    index: usize,
+}
+
+impl<'a> ReplayCursor<'a> {
+    /// Creates a cursor at the initial state of the given history.
+    pub fn new(history: &'a GameHistory) -> Self {
+        Self { history, index: 0 }
+    }
+
+    /// Fetches the index of the current state.
+    pub fn index(&self) -> usize { self.index }
+
+    /// Fetches the current state (if the replay is not empty).
+    pub fn state(&self) -> Option<&'a State> { self.history.states().get(self.index) }
+
+    /// Fetches the move that led to the current state.
+    pub fn last_move(&self) -> Option<Move> {
+        self.index.checked_sub(1).and_then(|i| self.history.moves().get(i).copied())
+    }
+
+    /// Fetches the move that leads to the next state.
+    pub fn next_move(&self) -> Option<Move> { self.history.moves().get(self.index).copied() }
+
+    /// Whether the cursor is at the initial state.
+    pub fn is_at_start(&self) -> bool { self.index == 0 }
+
+    /// Whether the cursor is at the final state.
+    pub fn is_at_end(&self) -> bool { self.index + 1 >= self.history.states().len() }
+
+    /// Steps to the next state, returning it if there is one.
+    pub fn step_forward(&mut self) -> Option<&'a State> {
+        if self.is_at_end() {
+            return None;
+        }
+        self.index += 1;
+        self.state()
+    }
+
+    /// Steps to the previous state, returning it if there is one.
+    pub fn step_backward(&mut self) -> Option<&'a State> {
+        if self.is_at_start() {
+            return None;
+        }
+        self.index -= 1;
+        self.state()
+    }
+
+    /// Moves to the state with the given index, clamped to the history's bounds.
+    pub fn seek(&mut self, index: usize) {
+        self.index = index.min(self.history.states().len().saturating_sub(1));
+    }
+
+    /// Moves to the initial state.
+    pub fn seek_start(&mut self) { self.seek(0) }
+
+    /// Moves to the final state.
+    pub fn seek_end(&mut self) { self.seek(usize::MAX) }
+}
+
+impl<'a> Iterator for ReplayCursor<'a> {
+    type Item = &'a State;
+
+    fn next(&mut self) -> Option<&'a State> {
+        self.step_forward()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game::{Board, Field, State, Team, BOARD_FIELDS}, protocol::{Event, EventPayload}, replay::GameReplay, util::Element};
+
+    #[test]
+    fn test_stepping() {
+        let mut state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let mut states = vec![state];
+        for _ in 0..3 {
+            state.perform(state.possible_moves()[0]);
+            states.push(state);
+        }
+        let protocol = Element::new("protocol")
+            .childs(states.iter().map(|&s| Element::from(Event::Room { room_id: "r".to_owned(), payload: EventPayload::Memento(s) })))
+            .build();
+        let replay = GameReplay::try_from(&protocol).unwrap();
+
+        let mut cursor = replay.cursor();
+        assert!(cursor.is_at_start());
+        assert_eq!(cursor.state(), Some(&states[0]));
+        assert_eq!(cursor.step_backward(), None);
+        assert_eq!(cursor.last_move(), None);
+        assert_eq!(cursor.next_move(), states[1].last_move());
+        assert_eq!(cursor.step_forward(), Some(&states[1]));
+        assert_eq!(cursor.last_move(), states[1].last_move());
+        assert_eq!(cursor.by_ref().collect::<Vec<_>>(), states[2..].iter().collect::<Vec<_>>());
+        assert!(cursor.is_at_end());
+        assert_eq!(cursor.step_backward(), Some(&states[2]));
+        cursor.seek_end();
+        assert_eq!(cursor.index(), 3);
+        cursor.seek_start();
+        assert_eq!(cursor.state(), Some(&states[0]));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use log::info;
+use quick_xml::Writer;
+use quick_xml::events::{Event as XmlEvent, BytesStart, BytesEnd};
+use crate::game::{State, Move};
+use crate::protocol::{Event, EventPayload, GameResult, Request, RequestPayload};
+use crate::util::{Element, Result};
+
+/// Writes the messages of a game to a replay, using the format of the
+/// official server's replays, i.e. a `<protocol>` stream of room messages
+/// containing the mementos and the result.
+pub struct ReplayRecorder {
+    room_id: String,
+    writer: Writer<ReplayWriter>,
+}
+
+/// The stream a replay is written to.
+enum ReplayWriter {
+    Plain(Box<dyn Write + Send>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Write for ReplayWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gzip(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gzip(w) => w.flush(),
+        }
+    }
+}
+
+impl ReplayRecorder {
+    /// Creates a recorder for the given room writing to the given stream.
+    pub fn new(room_id: &str, write: impl Write + Send + 'static) -> Result<Self> {
+        Self::with_writer(room_id, ReplayWriter::Plain(Box::new(write)))
+    }
+
+    /// Creates a recorder for the given room, opening the protocol.
+    fn with_writer(room_id: &str, writer: ReplayWriter) -> Result<Self> {
+        let mut writer = Writer::new(writer);
+        writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
+        Ok(Self { room_id: room_id.to_owned(), writer })
+    }
+
+    /// Creates a replay file for the given room in the given directory, named
+    /// by the room id and the current timestamp. If `compress` is set, the
+    /// file is gzip-compressed (and named `.xml.gz` instead of `.xml`).
+    pub fn create(directory: &Path, room_id: &str, compress: bool) -> Result<Self> {
+        fs::create_dir_all(directory)?;
+        let path = Self::path(directory, room_id, compress);
+        info!("Recording replay to {}", path.display());
+        let file = BufWriter::new(File::create(path)?);
+        if compress {
+            Self::with_writer(room_id, ReplayWriter::Gzip(GzEncoder::new(file, Compression::default())))
+        } else {
+            Self::new(room_id, file)
+        }
+    }
+
+    /// The path of a replay of the given room recorded now.
+    fn path(directory: &Path, room_id: &str, compress: bool) -> PathBuf {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let room_id: String = room_id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+        let extension = if compress { "xml.gz" } else { "xml" };
+        directory.join(format!("{}_{}.{}", room_id, timestamp, extension))
+    }
+
+    /// Records a received memento.
+    pub fn record_memento(&mut self, state: &State) -> Result<()> {
+        self.record_event(EventPayload::Memento(*state))
+    }
+
+    /// Records a sent move as the room message the client sent. Note that the
+    /// official server's replays do not contain such messages, hence replays
+    /// containing moves are not in the official format (although `GameReplay`
+    /// can load them).
+    pub fn record_move(&mut self, sent_move: Move) -> Result<()> {
+        let request = Request::Room { room_id: self.room_id.clone(), payload: RequestPayload::Move(sent_move) };
+        Element::from(request).write_to(&mut self.writer)
+    }
+
+    /// Records the game's result.
+    pub fn record_result(&mut self, result: &GameResult) -> Result<()> {
+        self.record_event(EventPayload::GameResult(result.clone()))
+    }
+
+    /// Closes the protocol and flushes the replay, finishing the compressed stream, if any.
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.write_event(XmlEvent::End(BytesEnd::borrowed(b"protocol")))?;
+        match self.writer.into_inner() {
+            ReplayWriter::Plain(mut w) => w.flush()?,
+            ReplayWriter::Gzip(w) => w.finish()?.flush()?,
+        }
+        Ok(())
+    }
+
+    /// Records a received room message.
+    fn record_event(&mut self, payload: EventPayload) -> Result<()> {
+        let event = Event::Room { room_id: self.room_id.clone(), payload };
+        Element::from(event).write_to(&mut self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs::{self, File}, io::Read, str::FromStr};
+
+    use flate2::read::GzDecoder;
+
+    use crate::{game::{Board, Field, State, Team, BOARD_FIELDS}, protocol::{Event, EventPayload, Request, RequestPayload}, server::game_result, util::Element};
+
+    use super::ReplayRecorder;
+
+    #[test]
+    fn test_record_compressed() {
+        let directory = env::temp_dir().join(format!("socha-replay-test-{}", std::process::id()));
+        let state = State::new(Board::new([Field::with_fish(1); BOARD_FIELDS]), 0, [0, 0], None, Team::One);
+        let m = state.possible_moves()[0];
+        let result = game_result(&state.child(m), None);
+
+        let mut recorder = ReplayRecorder::create(&directory, "a/b", true).unwrap();
+        recorder.record_memento(&state).unwrap();
+        recorder.record_move(m).unwrap();
+        recorder.record_memento(&state.child(m)).unwrap();
+        recorder.record_result(&result).unwrap();
+        recorder.finish().unwrap();
+
+        let paths: Vec<_> = fs::read_dir(&directory).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(paths.len(), 1);
+        let name = paths[0].file_name().unwrap().to_str().unwrap().to_owned();
+        assert!(name.starts_with("a_b_") && name.ends_with(".xml.gz"), "Unexpected name {}", name);
+        let mut xml = String::new();
+        GzDecoder::new(File::open(&paths[0]).unwrap()).read_to_string(&mut xml).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let protocol = Element::from_str(&xml).unwrap();
+        assert_eq!(protocol.name(), "protocol");
+        let room = |payload| Element::from(Event::Room { room_id: "a/b".to_owned(), payload });
+        assert_eq!(protocol.childs_by_name("room").cloned().collect::<Vec<_>>(), vec![
+            room(EventPayload::Memento(state)),
+            Element::from(Request::Room { room_id: "a/b".to_owned(), payload: RequestPayload::Move(m) }),
+            room(EventPayload::Memento(state.child(m))),
+            room(EventPayload::GameResult(result)),
+        ]);
+    }
+}
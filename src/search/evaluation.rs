@@ -0,0 +1,28 @@
+use crate::game::State;
+
+/// A static evaluation of game states, used by the search
+/// at leaf nodes.
+pub trait Evaluation {
+    /// Evaluates the given (non-terminal) state from the perspective
+    /// of the current team, i.e. higher scores are better for the team
+    /// returned by `State::current_team`.
+    fn evaluate(&self, state: &State) -> i32;
+}
+
+/// An evaluation that scores a state by the difference between
+/// the current team's fish and the opponent's fish.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FishDifference;
+
+impl Evaluation for FishDifference {
+    fn evaluate(&self, state: &State) -> i32 {
+        let team = state.current_team();
+        state.fish(team) as i32 - state.fish(team.opponent()) as i32
+    }
+}
+
+impl<F> Evaluation for F where F: Fn(&State) -> i32 {
+    fn evaluate(&self, state: &State) -> i32 {
+        self(state)
+    }
+}
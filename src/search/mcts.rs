@@ -0,0 +1,291 @@
+use std::{cmp::Ordering, time::Instant};
+
+use log::{info, debug};
+
+use crate::{game::{State, Move, Team}, client::{GameClientDelegate, TimeBudget}};
+
+use super::{RolloutPolicy, MctsBudget};
+
+/// The number of iterations performed per call to `ponder`.
+const PONDER_ITERATIONS: usize = 64;
+
+/// The maximum number of nodes grown while pondering.
+const MAX_PONDER_NODES: usize = 1_000_000;
+
+/// A node in the search tree.
+#[derive(Debug, Clone)]
+struct Node {
+    /// The move leading to this node, None for the root.
+    m: Option<Move>,
+    /// The team that performed the move leading to this node.
+    team: Option<Team>,
+    /// The index of the parent node.
+    parent: Option<usize>,
+    /// The indices of the expanded child nodes.
+    children: Vec<usize>,
+    /// The moves that have not been expanded yet.
+    untried: Vec<Move>,
+    /// The number of times this node was visited.
+    visits: u32,
+    /// The accumulated reward from the perspective of `team`.
+    reward: f64,
+}
+
+impl Node {
+    fn new(m: Option<Move>, team: Option<Team>, parent: Option<usize>, state: &State) -> Self {
+        Self { m, team, parent, children: Vec::new(), untried: state.possible_moves(), visits: 0, reward: 0.0 }
+    }
+
+    /// The upper confidence bound of this node given the number of visits of its parent.
+    fn uct(&self, parent_visits: u32, exploration: f64) -> f64 {
+        let visits = self.visits as f64;
+        self.reward / visits + exploration * ((parent_visits as f64).ln() / visits).sqrt()
+    }
+}
+
+/// A Monte Carlo tree search using UCT for selection and
+/// the given policy for rollouts. The tree is kept between
+/// searches and reused if the game continues from its root
+/// or one of the root's children, e.g. after pondering.
+pub struct Mcts<P> where P: RolloutPolicy {
+    policy: P,
+    budget: MctsBudget,
+    exploration: f64,
+    tree: Option<(State, Vec<Node>)>,
+}
+
+impl<P> Mcts<P> where P: RolloutPolicy {
+    /// Creates a new tree search with the given rollout policy and budget.
+    pub fn new(policy: P, budget: MctsBudget) -> Self {
+        Self { policy, budget, exploration: std::f64::consts::SQRT_2, tree: None }
+    }
+
+    /// Sets the exploration constant of the UCT formula (defaults to √2).
+    pub fn with_exploration(self, exploration: f64) -> Self {
+        Self { exploration, ..self }
+    }
+
+    /// Fetches the rollout policy.
+    pub fn policy(&self) -> &P { &self.policy }
+
+    /// Fetches the budget.
+    pub fn budget(&self) -> MctsBudget { self.budget }
+
+    /// Searches the best move in the given state, i.e. the most visited
+    /// child of the root. Returns None if there are no possible moves.
+    pub fn search(&mut self, state: &State) -> Option<Move> {
+        self.search_until(state, None)
+    }
+
+    /// Searches the best move in the given state, additionally
+    /// stopping at the given deadline.
+    pub fn search_until(&mut self, state: &State, deadline: Option<Instant>) -> Option<Move> {
+        let mut nodes = self.take_tree(state);
+        let start = Instant::now();
+        let mut iterations = 0;
+
+        while iterations == 0 || (match self.budget {
+            MctsBudget::Iterations(n) => iterations < n,
+            MctsBudget::Time(t) => start.elapsed() < t,
+        } && deadline.is_none_or(|d| Instant::now() < d)) {
+            self.iterate(&mut nodes, state);
+            iterations += 1;
+        }
+
+        let best = nodes[0].children
+            .iter()
+            .map(|&i| &nodes[i])
+            .max_by_key(|n| n.visits)?;
+        debug!("Performed {} iterations with {} nodes, best move has {} visits (mean reward: {:.3})",
+            iterations, nodes.len(), best.visits, best.reward / best.visits as f64);
+        let best_move = best.m;
+        self.tree = Some((*state, nodes));
+        best_move
+    }
+
+    /// Takes the kept tree if it is rooted at the given state or one of the
+    /// root's children (in which case the child's subtree is extracted),
+    /// otherwise creates a new tree.
+    fn take_tree(&mut self, state: &State) -> Vec<Node> {
+        match self.tree.take() {
+            Some((root, nodes)) if root == *state => nodes,
+            Some((root, nodes)) => nodes[0].children
+                .iter()
+                .find(|&&i| root.child(nodes[i].m.unwrap()) == *state)
+                .map(|&i| Self::subtree(&nodes, i))
+                .unwrap_or_else(|| vec![Node::new(None, None, None, state)]),
+            None => vec![Node::new(None, None, None, state)],
+        }
+    }
+
+    /// Copies the subtree rooted at the given node into a new tree.
+    fn subtree(nodes: &[Node], root: usize) -> Vec<Node> {
+        let mut subtree = vec![Node { m: None, team: None, parent: None, ..nodes[root].clone() }];
+        let mut next = 0;
+        while next < subtree.len() {
+            let children = std::mem::take(&mut subtree[next].children);
+            for i in children {
+                subtree.push(Node { parent: Some(next), ..nodes[i].clone() });
+                let child = subtree.len() - 1;
+                subtree[next].children.push(child);
+            }
+            next += 1;
+        }
+        subtree
+    }
+
+    /// Performs a single iteration of selection, expansion, rollout and backpropagation.
+    fn iterate(&mut self, nodes: &mut Vec<Node>, root_state: &State) {
+        let mut state = *root_state;
+        let mut current = 0;
+
+        // Selection
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            let parent_visits = nodes[current].visits;
+            current = *nodes[current].children
+                .iter()
+                .max_by(|&&a, &&b| nodes[a].uct(parent_visits, self.exploration)
+                    .partial_cmp(&nodes[b].uct(parent_visits, self.exploration))
+                    .unwrap_or(Ordering::Equal))
+                .unwrap();
+            state.perform(nodes[current].m.unwrap());
+        }
+
+        // Expansion
+        if let Some(m) = nodes[current].untried.pop() {
+            let team = state.current_team();
+            state.perform(m);
+            nodes.push(Node::new(Some(m), Some(team), Some(current), &state));
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+        }
+
+        // Rollout
+        loop {
+            let moves = state.possible_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let m = self.policy.choose(&state, &moves);
+            state = state.child(m);
+        }
+
+        // Backpropagation
+        let mut next = Some(current);
+        while let Some(i) = next {
+            let node = &mut nodes[i];
+            node.visits += 1;
+            if let Some(team) = node.team {
+                node.reward += Self::reward(&state, team);
+            }
+            next = node.parent;
+        }
+    }
+
+    /// The reward of a finished game for the given team, based on the fish difference.
+    fn reward(state: &State, team: Team) -> f64 {
+        match state.fish(team).cmp(&state.fish(team.opponent())) {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        }
+    }
+}
+
+impl<P> GameClientDelegate for Mcts<P> where P: RolloutPolicy {
+    fn request_move(&mut self, state: &State, _my_team: Team, budget: TimeBudget) -> Move {
+        info!("Requested move");
+        let chosen_move = self.search_until(state, Some(budget.deadline())).expect("No move found!");
+        info!("Chose move {}", chosen_move);
+        chosen_move
+    }
+
+    fn ponder(&mut self, state: &State, _my_team: Team) -> bool {
+        let mut nodes = self.take_tree(state);
+        for _ in 0..PONDER_ITERATIONS {
+            self.iterate(&mut nodes, state);
+        }
+        let keep_pondering = nodes.len() < MAX_PONDER_NODES;
+        self.tree = Some((*state, nodes));
+        keep_pondering
+    }
+
+    fn on_ponder_end(&mut self, opponent_move: Option<Move>) {
+        match (self.tree.take(), opponent_move) {
+            (Some((root, nodes)), Some(m)) if root.validate(m).is_ok() => {
+                debug!("Pondered with {} visits", nodes[0].visits);
+                let next = root.child(m);
+                self.tree = Some((root, nodes));
+                let nodes = self.take_tree(&next);
+                self.tree = Some((next, nodes));
+            },
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{game::{Board, State, Team, Vec2, Doubled, Field, BOARD_FIELDS}, search::{MctsBudget, RandomRollout, GreedyRollout}, client::{GameClientDelegate, TimeBudget}};
+
+    use super::Mcts;
+
+    #[test]
+    fn test_finds_winning_move() {
+        // Team One only wins by taking the 4 fish, otherwise Two would take them
+        let board = indoc! {r#"
+            R1000000
+            40000000
+            RB000000
+            00000000
+            R000000B
+            0000000B
+            R000000B
+            00000030
+        "#}.parse::<Board>().unwrap();
+        let state = State::new(board, 8, [0, 0], None, Team::One);
+        let mut mcts = Mcts::new(RandomRollout::seeded(1), MctsBudget::Iterations(500));
+        assert_eq!(mcts.search(&state).map(|m| m.to()), Some(Vec2::<Doubled>::new(1, 1)));
+    }
+
+    #[test]
+    fn test_request_move_is_legal() {
+        let board = Board::new([Field::with_fish(1); BOARD_FIELDS]);
+        let state = State::new(board, 0, [0, 0], None, Team::One);
+        let mut mcts = Mcts::new(GreedyRollout::seeded(2), MctsBudget::Iterations(50));
+        let m = mcts.request_move(&state, Team::One, TimeBudget::default());
+        assert!(state.possible_moves().contains(&m));
+    }
+
+    #[test]
+    fn test_ponder_reuses_tree() {
+        let board = Board::new([Field::with_fish(1); BOARD_FIELDS]);
+        let state = State::new(board, 0, [0, 0], None, Team::One);
+        let mut mcts = Mcts::new(RandomRollout::seeded(3), MctsBudget::Iterations(50));
+        let m = mcts.request_move(&state, Team::One, TimeBudget::default());
+
+        let next = state.child(m);
+        for _ in 0..3 {
+            assert!(mcts.ponder(&next, Team::One));
+        }
+        let (root, nodes) = mcts.tree.as_ref().unwrap();
+        assert_eq!(*root, next);
+        let opponent_move = nodes[nodes[0].children[0]].m.unwrap();
+        let opponent_visits = nodes[nodes[0].children[0]].visits;
+        assert!(nodes[0].visits > 3 * 64);
+
+        mcts.on_ponder_end(Some(opponent_move));
+        let after = next.child(opponent_move);
+        let (root, nodes) = mcts.tree.as_ref().unwrap();
+        assert_eq!(*root, after);
+        assert_eq!(nodes[0].visits, opponent_visits);
+        assert!(nodes.iter().enumerate().skip(1).all(|(i, n)| n.parent.is_some_and(|p| nodes[p].children.contains(&i))));
+
+        let m = mcts.request_move(&after, Team::One, TimeBudget::default());
+        assert!(after.possible_moves().contains(&m));
+        assert_eq!(mcts.tree.as_ref().unwrap().1[0].visits, opponent_visits + 50);
+    }
+}
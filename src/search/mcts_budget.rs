@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+/// The amount of work a Monte Carlo tree search may spend per move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MctsBudget {
+    /// Performs the given number of iterations.
+    Iterations(usize),
+    /// Performs iterations until the given time has elapsed.
+    Time(Duration),
+}
+
+impl Default for MctsBudget {
+    fn default() -> Self {
+        Self::Time(Duration::from_millis(1500))
+    }
+}
@@ -0,0 +1,15 @@
+mod evaluation;
+mod mcts;
+mod mcts_budget;
+mod rollout_policy;
+mod search_limits;
+mod search_result;
+mod searcher;
+
+pub use evaluation::*;
+pub use mcts::*;
+pub use mcts_budget::*;
+pub use rollout_policy::*;
+pub use search_limits::*;
+pub use search_result::*;
+pub use searcher::*;
@@ -0,0 +1,93 @@
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::game::{State, Move};
+
+/// A policy for choosing moves during the rollout
+/// (simulation) phase of a Monte Carlo tree search.
+pub trait RolloutPolicy {
+    /// Chooses one of the given (non-empty) possible moves in the given state.
+    fn choose(&mut self, state: &State, moves: &[Move]) -> Move;
+}
+
+/// A rollout policy that picks moves uniformly at random.
+#[derive(Debug, Clone)]
+pub struct RandomRollout<R = StdRng> where R: Rng {
+    rng: R,
+}
+
+impl RandomRollout {
+    /// Creates a new random rollout policy seeded from the system's entropy.
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a new random rollout policy with the given seed.
+    pub fn seeded(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for RandomRollout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> RandomRollout<R> where R: Rng {
+    /// Creates a new random rollout policy using the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl<R> RolloutPolicy for RandomRollout<R> where R: Rng {
+    fn choose(&mut self, _state: &State, moves: &[Move]) -> Move {
+        *moves.choose(&mut self.rng).expect("No moves to choose from")
+    }
+}
+
+/// A rollout policy that picks the move collecting the most
+/// fish, breaking ties at random.
+#[derive(Debug, Clone)]
+pub struct GreedyRollout<R = StdRng> where R: Rng {
+    rng: R,
+}
+
+impl GreedyRollout {
+    /// Creates a new greedy rollout policy seeded from the system's entropy.
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a new greedy rollout policy with the given seed.
+    pub fn seeded(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GreedyRollout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> GreedyRollout<R> where R: Rng {
+    /// Creates a new greedy rollout policy using the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl<R> RolloutPolicy for GreedyRollout<R> where R: Rng {
+    fn choose(&mut self, state: &State, moves: &[Move]) -> Move {
+        let max_fish = moves.iter().map(|m| state.board()[m.to()].fish()).max().expect("No moves to choose from");
+        let best: Vec<_> = moves.iter().filter(|m| state.board()[m.to()].fish() == max_fish).collect();
+        **best.choose(&mut self.rng).unwrap()
+    }
+}
+
+impl<F> RolloutPolicy for F where F: FnMut(&State, &[Move]) -> Move {
+    fn choose(&mut self, state: &State, moves: &[Move]) -> Move {
+        self(state, moves)
+    }
+}
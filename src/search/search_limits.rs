@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+/// The limits of a search, i.e. when iterative deepening stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    max_depth: usize,
+    time: Option<Duration>,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self::depth(usize::MAX)
+    }
+}
+
+impl SearchLimits {
+    /// Creates new search limits.
+    #[inline]
+    pub fn new(max_depth: usize, time: Option<Duration>) -> Self {
+        Self { max_depth, time }
+    }
+
+    /// Limits the search to the given depth (in plies).
+    #[inline]
+    pub fn depth(max_depth: usize) -> Self {
+        Self::new(max_depth, None)
+    }
+
+    /// Limits the search to the given amount of time.
+    #[inline]
+    pub fn time(time: Duration) -> Self {
+        Self::new(usize::MAX, Some(time))
+    }
+
+    /// Additionally limits the search to the given depth.
+    #[inline]
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Additionally limits the search to the given amount of time.
+    #[inline]
+    pub fn with_time(self, time: Duration) -> Self {
+        Self { time: Some(time), ..self }
+    }
+
+    /// The maximum depth (in plies).
+    #[inline]
+    pub fn max_depth(&self) -> usize { self.max_depth }
+
+    /// The maximum amount of time, if any.
+    #[inline]
+    pub fn time_limit(&self) -> Option<Duration> { self.time }
+}
@@ -0,0 +1,38 @@
+use crate::game::Move;
+
+/// The outcome of a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    principal_variation: Vec<Move>,
+    score: i32,
+    depth: usize,
+    nodes: u64,
+}
+
+impl SearchResult {
+    /// Creates a new search result.
+    #[inline]
+    pub fn new(principal_variation: Vec<Move>, score: i32, depth: usize, nodes: u64) -> Self {
+        Self { principal_variation, score, depth, nodes }
+    }
+
+    /// The best move found, if the position has any moves.
+    #[inline]
+    pub fn best_move(&self) -> Option<Move> { self.principal_variation.first().copied() }
+
+    /// The expected line of play, starting with the best move.
+    #[inline]
+    pub fn principal_variation(&self) -> &Vec<Move> { &self.principal_variation }
+
+    /// The score of the best move from the perspective of the current team.
+    #[inline]
+    pub fn score(&self) -> i32 { self.score }
+
+    /// The depth of the last completed iteration.
+    #[inline]
+    pub fn depth(&self) -> usize { self.depth }
+
+    /// The number of visited nodes.
+    #[inline]
+    pub fn nodes(&self) -> u64 { self.nodes }
+}
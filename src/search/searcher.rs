@@ -0,0 +1,242 @@
+use std::{cmp::Reverse, time::Instant};
+
+use log::{info, debug};
+
+use crate::{game::{State, Move, Team}, client::{GameClientDelegate, TimeBudget}};
+
+use super::{Evaluation, SearchLimits, SearchResult};
+
+/// The score of a won game. Terminal states are scored as this plus the fish difference.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// An upper bound on all scores.
+const INFINITY: i32 = i32::MAX;
+
+/// The number of nodes between two checks of the clock.
+const NODES_PER_TIME_CHECK: u64 = 1024;
+
+/// A negamax search with alpha-beta pruning, iterative deepening
+/// and move ordering, operating on the game state in place.
+pub struct Searcher<E> where E: Evaluation {
+    evaluation: E,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    cut_off: bool,
+}
+
+impl<E> Searcher<E> where E: Evaluation {
+    /// Creates a new searcher using the given evaluation.
+    pub fn new(evaluation: E) -> Self {
+        Self { evaluation, nodes: 0, deadline: None, aborted: false, cut_off: false }
+    }
+
+    /// Fetches the evaluation.
+    pub fn evaluation(&self) -> &E { &self.evaluation }
+
+    /// Searches the best move in the given state within the given limits.
+    pub fn search(&mut self, state: &State, limits: SearchLimits) -> SearchResult {
+        self.nodes = 0;
+        self.deadline = limits.time_limit().map(|t| Instant::now() + t);
+        self.aborted = false;
+
+        let mut state = *state;
+        let mut result = SearchResult::new(
+            state.possible_moves().into_iter().take(1).collect(),
+            self.evaluate(&state),
+            0,
+            0,
+        );
+
+        for depth in 1..=limits.max_depth() {
+            let mut pv = Vec::new();
+            self.cut_off = false;
+            let score = self.negamax(&mut state, depth, -INFINITY, INFINITY, result.principal_variation(), &mut pv);
+            if self.aborted {
+                break;
+            }
+            debug!("Searched depth {} (score: {}, nodes: {}, pv: {:?})", depth, score, self.nodes, pv);
+            result = SearchResult::new(pv, score, depth, self.nodes);
+            if !self.cut_off || score.abs() >= WIN_SCORE {
+                // The whole game tree has been searched or the outcome is decided
+                break;
+            }
+        }
+
+        SearchResult::new(result.principal_variation().clone(), result.score(), result.depth(), self.nodes)
+    }
+
+    /// Evaluates the given state from the perspective of the current team.
+    fn evaluate(&self, state: &State) -> i32 {
+        if state.is_over() {
+            let team = state.current_team();
+            let difference = state.fish(team) as i32 - state.fish(team.opponent()) as i32;
+            difference.signum() * WIN_SCORE + difference
+        } else {
+            self.evaluation.evaluate(state)
+        }
+    }
+
+    /// Whether the search should be aborted due to the deadline.
+    fn should_abort(&mut self) -> bool {
+        if !self.aborted && self.nodes.is_multiple_of(NODES_PER_TIME_CHECK) {
+            self.aborted = self.deadline.is_some_and(|d| Instant::now() >= d);
+        }
+        self.aborted
+    }
+
+    /// Orders the moves, trying the move from the previous principal variation
+    /// first and then preferring moves onto fields with more fish.
+    fn order_moves(&self, state: &State, moves: &mut [Move], pv_move: Option<Move>) {
+        moves.sort_by_key(|&m| (Reverse(Some(m) == pv_move), Reverse(state.board()[m.to()].fish())));
+    }
+
+    /// Searches the given state to the given depth, returning its score from the
+    /// perspective of the current team and writing the principal variation to `pv`.
+    fn negamax(&mut self, state: &mut State, depth: usize, mut alpha: i32, beta: i32, previous_pv: &[Move], pv: &mut Vec<Move>) -> i32 {
+        self.nodes += 1;
+        if self.should_abort() {
+            return 0;
+        }
+
+        if depth == 0 {
+            // Remember that the tree was cut off before the game ended
+            self.cut_off |= !state.is_over();
+            return self.evaluate(state);
+        }
+
+        let mut moves = state.possible_moves();
+        if moves.is_empty() {
+            return self.evaluate(state);
+        }
+
+        let team = state.current_team();
+        self.order_moves(state, &mut moves, previous_pv.first().copied());
+
+        let mut best = -INFINITY;
+        let mut child_pv = Vec::new();
+        for m in moves {
+            // Only follow the previous principal variation along its own line
+            let child_previous_pv = if previous_pv.first() == Some(&m) { &previous_pv[1..] } else { &[] };
+            child_pv.clear();
+
+            let undo = state.perform(m);
+            // Teams that cannot move are skipped, in which case the same team moves again
+            let score = if state.current_team() == team {
+                self.negamax(state, depth - 1, alpha, beta, child_previous_pv, &mut child_pv)
+            } else {
+                -self.negamax(state, depth - 1, -beta, -alpha, child_previous_pv, &mut child_pv)
+            };
+            state.unperform(undo);
+
+            if self.aborted {
+                return 0;
+            }
+            if score > best {
+                best = score;
+                pv.clear();
+                pv.push(m);
+                pv.extend_from_slice(&child_pv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
+}
+
+impl<E> GameClientDelegate for Searcher<E> where E: Evaluation {
+    fn request_move(&mut self, state: &State, _my_team: Team, budget: TimeBudget) -> Move {
+        info!("Requested move");
+        let result = self.search(state, SearchLimits::time(budget.remaining()));
+        let chosen_move = result.best_move().expect("No move found!");
+        info!("Chose move {} (score: {}, depth: {})", chosen_move, result.score(), result.depth());
+        chosen_move
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use indoc::indoc;
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{game::{Board, State, Team, Vec2, Doubled, Field, BOARD_FIELDS, MAX_FISH}, search::{SearchLimits, FishDifference, Evaluation}};
+
+    use super::Searcher;
+
+    /// A plain negamax without pruning for reference.
+    fn minimax(state: &State, depth: usize) -> i32 {
+        let moves = state.possible_moves();
+        if depth == 0 || moves.is_empty() {
+            return Searcher::new(FishDifference).evaluate(state);
+        }
+        let team = state.current_team();
+        moves.into_iter()
+            .map(|m| {
+                let child = state.child(m);
+                let score = minimax(&child, depth - 1);
+                if child.current_team() == team { score } else { -score }
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prefers_more_fish() {
+        let board = indoc! {r#"
+            R1000000
+            40000000
+            R0000000
+            00000000
+            R000000B
+            0000000B
+            R000000B
+            0000002B
+        "#}.parse::<Board>().unwrap();
+        let state = State::new(board, 8, [0, 0], None, Team::One);
+        let result = Searcher::new(FishDifference).search(&state, SearchLimits::depth(1));
+        assert_eq!(result.best_move().map(|m| m.to()), Some(Vec2::<Doubled>::new(1, 1)));
+        assert_eq!(result.score(), 4);
+    }
+
+    #[test]
+    fn test_alpha_beta_matches_minimax() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..5 {
+            let board = Board::new([(); BOARD_FIELDS].map(|_| Field::with_fish(rng.gen_range(0..=MAX_FISH))));
+            let mut state = State::new(board, 0, [0, 0], None, Team::One);
+            for _ in 0..rng.gen_range(8..20) {
+                if let Some(&m) = state.possible_moves().choose(&mut rng) {
+                    state.perform(m);
+                }
+            }
+            let depth = 3;
+            let result = Searcher::new(FishDifference).search(&state, SearchLimits::depth(depth));
+            assert_eq!(result.score(), minimax(&state, result.depth()));
+            if let Some(m) = result.best_move() {
+                let child = state.child(m);
+                let score = minimax(&child, result.depth() - 1);
+                assert_eq!(if child.current_team() == state.current_team() { score } else { -score }, result.score());
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let board = Board::new([Field::with_fish(1); BOARD_FIELDS]);
+        let state = State::new(board, 0, [0, 0], None, Team::One);
+        let result = Searcher::new(FishDifference).search(&state, SearchLimits::time(Duration::from_millis(50)));
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn test_closure_evaluation() {
+        let evaluation = |state: &State| state.turn() as i32;
+        assert_eq!(evaluation.evaluate(&State::new(Board::EMPTY, 3, [0, 0], None, Team::One)), 3);
+    }
+}
@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use log::{debug, info, warn};
+use quick_xml::events::{Event as XmlEvent, BytesStart, BytesEnd};
+use quick_xml::{Reader, Writer};
+use rand::Rng;
+
+use crate::client::{GameClient, GameClientDelegate, DebugMode};
+use crate::game::{State, Team, Move, Board, TEAMS};
+use crate::protocol::{Request, RequestPayload, Event, EventPayload, GameResult, ScoreDefinition, ScoreDefinitionFragment, ScoreAggregation, Player, Score, ScoreCause};
+use crate::util::{Element, Error, Result};
+
+use super::pipe;
+
+/// The id of the (single) room hosted by the local server.
+const ROOM_ID: &str = "local";
+
+/// A connection to a client.
+struct Connection<'a> {
+    team: Team,
+    reader: Reader<BufReader<Box<dyn Read + 'a>>>,
+    writer: Writer<BufWriter<Box<dyn Write + 'a>>>,
+}
+
+impl<'a> Connection<'a> {
+    fn new(team: Team, read: impl Read + 'a, write: impl Write + 'a) -> Self {
+        Self {
+            team,
+            reader: Reader::from_reader(BufReader::new(Box::new(read) as Box<dyn Read + 'a>)),
+            writer: Writer::new(BufWriter::new(Box::new(write) as Box<dyn Write + 'a>)),
+        }
+    }
+
+    /// Performs the handshake, i.e. exchanges `<protocol>` tags and handles the join request.
+    fn handshake(&mut self) -> Result<()> {
+        let mut buf = Vec::new();
+        loop {
+            match self.reader.read_event(&mut buf)? {
+                XmlEvent::Start(ref start) if start.name() == b"protocol" => break,
+                XmlEvent::Eof => return Err(Error::Eof),
+                _ => (),
+            }
+        }
+
+        let join_xml = Element::read_from(&mut self.reader)?;
+        match Request::try_from(&join_xml)? {
+            request @ (Request::Join | Request::JoinRoom { .. } | Request::JoinPrepared { .. }) => info!("Team {} joined with {:?}", self.team, request),
+            _ => return Err(Error::InvalidState(format!("Expected join request, got {}", join_xml))),
+        }
+
+        self.writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
+        self.send(Event::Joined { room_id: ROOM_ID.to_owned() })?;
+        self.send_room(EventPayload::Welcome(self.team))
+    }
+
+    /// Sends the given event.
+    fn send(&mut self, event: Event) -> Result<()> {
+        Element::from(event).write_to(&mut self.writer)
+    }
+
+    /// Sends the given payload as a room message.
+    fn send_room(&mut self, payload: EventPayload) -> Result<()> {
+        self.send(Event::Room { room_id: ROOM_ID.to_owned(), payload })
+    }
+
+    /// Receives a move from the client.
+    fn receive_move(&mut self) -> Result<Move> {
+        let elem = Element::read_from(&mut self.reader)?;
+        match Request::try_from(&elem)? {
+            Request::Room { payload: RequestPayload::Move(m), .. } => Ok(m),
+            _ => Err(Error::InvalidState(format!("Expected move, got {}", elem))),
+        }
+    }
+
+    /// Notifies the client that the room was left and closes the protocol.
+    /// Since clients may disconnect as soon as they left the room, failing
+    /// to close the protocol is not an error.
+    fn close(&mut self) -> Result<()> {
+        self.send(Event::Left { room_id: ROOM_ID.to_owned() })?;
+        let closed = self.writer.write_event(XmlEvent::End(BytesEnd::borrowed(b"protocol")))
+            .and_then(|_| Ok(self.writer.inner().flush()?));
+        if let Err(e) = closed {
+            debug!("Team {} disconnected before the protocol was closed: {:?}", self.team, e);
+        }
+        Ok(())
+    }
+}
+
+/// A local game server that hosts a single game between two
+/// clients, speaking the same XML protocol as the official server.
+/// Moves are validated using the game rules.
+pub struct LocalServer {
+    state: State,
+}
+
+impl LocalServer {
+    /// Creates a new server that starts the game from the given state.
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+
+    /// Creates a new server with a randomly generated board.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self::new(State::new(Board::generate(rng), 0, [0; TEAMS], None, Team::One))
+    }
+
+    /// Fetches the initial state.
+    pub fn state(&self) -> &State { &self.state }
+
+    /// Accepts two clients from the given listener and hosts a game between them.
+    /// The first client to connect plays team one.
+    pub fn serve_tcp(self, listener: &TcpListener) -> Result<GameResult> {
+        let (one, address) = listener.accept()?;
+        info!("Accepted {} as team {}", address, Team::One);
+        let (two, address) = listener.accept()?;
+        info!("Accepted {} as team {}", address, Team::Two);
+        self.run((one.try_clone()?, one), (two.try_clone()?, two))
+    }
+
+    /// Hosts a game between the given delegates, each running in a
+    /// client on its own thread and connected via in-memory pipes.
+    pub fn play<A, B>(self, one: A, two: B) -> Result<GameResult>
+    where A: GameClientDelegate + Send,
+          B: GameClientDelegate + Send {
+        let (to_one, from_server_one) = pipe();
+        let (to_server_one, from_one) = pipe();
+        let (to_two, from_server_two) = pipe();
+        let (to_server_two, from_two) = pipe();
+
+        thread::scope(|scope| {
+            let debug_mode = || DebugMode { debug_reader: false, debug_writer: false };
+            let client_one = scope.spawn(move || GameClient::new(one, debug_mode(), None).with_fallback(true).run(from_server_one, to_server_one));
+            let client_two = scope.spawn(move || GameClient::new(two, debug_mode(), None).with_fallback(true).run(from_server_two, to_server_two));
+            let result = self.run((from_one, to_one), (from_two, to_two));
+            for client in [client_one, client_two] {
+                if let Err(e) = client.join().expect("Client panicked") {
+                    warn!("Client failed: {:?}", e);
+                }
+            }
+            result
+        })
+    }
+
+    /// Hosts a game between the two clients connected via the given streams.
+    /// A client that disconnects during the game loses it (as having left).
+    pub fn run(self, one: (impl Read, impl Write), two: (impl Read, impl Write)) -> Result<GameResult> {
+        let mut connections = [
+            Connection::new(Team::One, one.0, one.1),
+            Connection::new(Team::Two, two.0, two.1),
+        ];
+        for connection in &mut connections {
+            connection.handshake()?;
+        }
+
+        let mut state = self.state;
+        let mut violation: Option<(Team, ScoreCause, String)> = None;
+
+        'game: loop {
+            for connection in &mut connections {
+                if let Err(e) = connection.send_room(EventPayload::Memento(state)) {
+                    violation = Some((connection.team, ScoreCause::Left, format!("{:?}", e)));
+                    break 'game;
+                }
+            }
+
+            if state.possible_moves().is_empty() {
+                break;
+            }
+
+            let team = state.current_team();
+            let connection = &mut connections[team.index()];
+            match connection.send_room(EventPayload::MoveRequest).and_then(|_| connection.receive_move()) {
+                Ok(m) => match state.try_perform(m) {
+                    Ok(_) => info!("Team {} performed {}", team, m),
+                    Err(e) => {
+                        violation = Some((team, ScoreCause::RuleViolation, format!("Invalid move {}: {}", m, e)));
+                        break;
+                    },
+                },
+                Err(e) => {
+                    violation = Some((team, ScoreCause::Left, format!("{:?}", e)));
+                    break;
+                },
+            }
+        }
+
+        // The clients may already be gone, e.g. after leaving the game
+        let result = game_result(&state, violation);
+        for connection in &mut connections {
+            if let Err(e) = connection.send_room(EventPayload::GameResult(result.clone())).and_then(|_| connection.close()) {
+                warn!("Could not send the result to team {}: {:?}", connection.team, e);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Computes the result of the game, optionally ended by a violation of the given team.
+pub(crate) fn game_result(state: &State, violation: Option<(Team, ScoreCause, String)>) -> GameResult {
+    let definition = ScoreDefinition::new([
+        ScoreDefinitionFragment::new("Siegpunkte", ScoreAggregation::Sum, true),
+        ScoreDefinitionFragment::new("∅ Punkte", ScoreAggregation::Average, true),
+    ]);
+    let winner = match &violation {
+        Some((team, _, _)) => Some(team.opponent()),
+        None => match state.fish(Team::One).cmp(&state.fish(Team::Two)) {
+            Ordering::Greater => Some(Team::One),
+            Ordering::Less => Some(Team::Two),
+            Ordering::Equal => None,
+        },
+    };
+    let scores: HashMap<_, _> = [Team::One, Team::Two].into_iter()
+        .map(|team| {
+            let points = match winner {
+                Some(w) if w == team => 2,
+                Some(_) => 0,
+                None => 1,
+            };
+            let (cause, reason) = match &violation {
+                Some((t, cause, reason)) if *t == team => (*cause, reason.as_str()),
+                _ => (ScoreCause::Regular, ""),
+            };
+            (Player::new(None, team), Score::new(cause, reason, [points, state.fish(team) as i32]))
+        })
+        .collect();
+    GameResult::new(definition, scores, winner.map(|w| Player::new(None, w)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::{BufReader, Write}, thread};
+
+    use quick_xml::{Reader, events::Event as XmlEvent};
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{client::{DebugMode, GameClient, GameClientDelegate, TimeBudget}, game::{State, Team, Move}, protocol::{Event, EventPayload, Player, Request, ScoreCause}, server::pipe, util::Element};
+
+    use super::LocalServer;
+
+    struct RandomLogic(StdRng);
+
+    impl GameClientDelegate for RandomLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            *state.possible_moves().choose(&mut self.0).unwrap()
+        }
+    }
+
+    struct InvalidLogic;
+
+    impl GameClientDelegate for InvalidLogic {
+        fn request_move(&mut self, state: &State, _my_team: Team, _budget: TimeBudget) -> Move {
+            let m = state.possible_moves()[0];
+            Move::new(m.from(), m.to() + m.to())
+        }
+    }
+
+    #[test]
+    fn test_play_in_memory() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let server = LocalServer::random(&mut rng);
+        let result = server.play(RandomLogic(StdRng::seed_from_u64(1)), RandomLogic(StdRng::seed_from_u64(2))).unwrap();
+        assert_eq!(result.scores().len(), 2);
+        assert!(result.scores().values().all(|s| s.cause() == ScoreCause::Regular));
+    }
+
+    #[test]
+    fn test_rule_violation() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let server = LocalServer::random(&mut rng);
+        let result = server.play(InvalidLogic, RandomLogic(StdRng::seed_from_u64(3))).unwrap();
+        assert_eq!(result.winner().as_ref().map(|p| p.team()), Some(Team::Two));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (to_one, from_server_one) = pipe();
+        let (to_server_one, from_one) = pipe();
+        let (to_two, from_server_two) = pipe();
+        let (mut to_server_two, from_two) = pipe();
+        let debug_mode = DebugMode { debug_reader: false, debug_writer: false };
+        let one = thread::spawn(move || GameClient::new(RandomLogic(StdRng::seed_from_u64(4)), debug_mode, None).run(from_server_one, to_server_one));
+
+        // The second client drops its streams after receiving the initial state
+        let two = thread::spawn(move || {
+            write!(to_server_two, "<protocol>{}", Element::from(Request::Join)).unwrap();
+            let mut reader = Reader::from_reader(BufReader::new(from_server_two));
+            let mut buf = Vec::new();
+            while !matches!(reader.read_event(&mut buf).unwrap(), XmlEvent::Start(ref s) if s.name() == b"protocol") {}
+            while !matches!(Event::try_from(&Element::read_from(&mut reader).unwrap()), Ok(Event::Room { payload: EventPayload::Memento(_), .. })) {}
+        });
+
+        let result = LocalServer::random(&mut StdRng::seed_from_u64(7)).run((from_one, to_one), (from_two, to_two)).unwrap();
+        two.join().unwrap();
+        assert_eq!(one.join().unwrap().unwrap(), result);
+        assert_eq!(result.winner().as_ref().map(|p| p.team()), Some(Team::One));
+        assert_eq!(result.scores()[&Player::new(None, Team::Two)].cause(), ScoreCause::Left);
+    }
+}
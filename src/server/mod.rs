@@ -0,0 +1,5 @@
+mod local_server;
+mod pipe;
+
+pub use local_server::*;
+pub use pipe::*;
@@ -0,0 +1,70 @@
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Sender, Receiver};
+
+/// The reading end of an in-memory pipe.
+pub struct PipeReader {
+    rx: Receiver<Vec<u8>>,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+/// The writing end of an in-memory pipe.
+#[derive(Clone)]
+pub struct PipeWriter {
+    tx: Sender<Vec<u8>>,
+}
+
+/// Creates a unidirectional in-memory pipe, e.g. for connecting
+/// a client to a local server on another thread. The reader
+/// reaches EOF once all writers are dropped.
+pub fn pipe() -> (PipeWriter, PipeReader) {
+    let (tx, rx) = mpsc::channel();
+    (PipeWriter { tx }, PipeReader { rx, buf: Vec::new(), pos: 0 })
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.buf.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.buf = chunk;
+                    self.pos = 0;
+                },
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.buf.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx.send(buf.to_vec()).map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::pipe;
+
+    #[test]
+    fn test_pipe() {
+        let (mut writer, mut reader) = pipe();
+        writer.write_all(b"Hello, ").unwrap();
+        writer.write_all(b"world!").unwrap();
+        drop(writer);
+        let mut s = String::new();
+        reader.read_to_string(&mut s).unwrap();
+        assert_eq!(s, "Hello, world!");
+    }
+}
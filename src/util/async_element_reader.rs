@@ -0,0 +1,173 @@
+use quick_xml::Reader;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::{Element, Error, Result};
+
+/// The number of bytes to read from the underlying stream at once.
+const CHUNK_SIZE: usize = 4096;
+
+/// Reads XML elements from an async stream. Since quick-xml only
+/// parses synchronously, incoming bytes are buffered until a complete
+/// top-level element is available, which is then parsed from the buffer.
+pub struct AsyncElementReader<R> where R: AsyncRead + Unpin {
+    read: R,
+    buf: Vec<u8>,
+}
+
+/// The outcome of scanning the buffer for the next top-level XML node.
+enum Scan {
+    /// The buffer does not contain a complete node yet.
+    Incomplete,
+    /// The buffer contains a complete element ending at the given position.
+    Element(usize),
+    /// The buffer contains the start tag with the given name, ending at the given position.
+    Start(Vec<u8>, usize),
+    /// The enclosing element was closed.
+    Closed,
+}
+
+impl<R> AsyncElementReader<R> where R: AsyncRead + Unpin {
+    /// Creates a new reader reading from the given stream.
+    pub fn new(read: R) -> Self {
+        Self { read, buf: Vec::new() }
+    }
+
+    /// Reads until the opening tag with the given name, e.g. `<protocol>`, skipping other nodes.
+    pub async fn read_start(&mut self, name: &str) -> Result<()> {
+        loop {
+            match self.scan(true) {
+                Scan::Start(start, end) => {
+                    self.buf.drain(..end);
+                    if start == name.as_bytes() {
+                        return Ok(());
+                    }
+                },
+                Scan::Element(end) => { self.buf.drain(..end); },
+                Scan::Closed => return Err(Error::Eof),
+                Scan::Incomplete => self.fill().await?,
+            }
+        }
+    }
+
+    /// Reads the next complete element.
+    pub async fn read_element(&mut self) -> Result<Element> {
+        loop {
+            match self.scan(false) {
+                Scan::Element(end) => {
+                    let element = Element::read_from(&mut Reader::from_reader(&self.buf[..end]));
+                    self.buf.drain(..end);
+                    return element;
+                },
+                Scan::Closed => return Err(Error::Eof),
+                Scan::Start(..) | Scan::Incomplete => self.fill().await?,
+            }
+        }
+    }
+
+    /// Reads more bytes from the stream into the buffer.
+    async fn fill(&mut self) -> Result<()> {
+        self.buf.reserve(CHUNK_SIZE);
+        match self.read.read_buf(&mut self.buf).await? {
+            0 => Err(Error::Eof),
+            _ => Ok(()),
+        }
+    }
+
+    /// Finds the end of the next top-level node in the buffer, optionally
+    /// stopping at start tags (rather than waiting for the whole element).
+    /// Since quick-xml treats truncated tags at the end of its input as
+    /// complete, the tags are delimited by a simple scan instead.
+    fn scan(&self, stop_at_start: bool) -> Scan {
+        let buf = &self.buf[..];
+        let mut i = 0;
+        let mut depth = 0usize;
+        loop {
+            let start = match buf[i..].iter().position(|&b| b == b'<') {
+                Some(offset) => i + offset,
+                None => return Scan::Incomplete,
+            };
+            // Comments and CDATA sections may contain unescaped angle brackets
+            let terminator: &[u8] = if buf[start..].starts_with(b"<!--") {
+                b"-->"
+            } else if buf[start..].starts_with(b"<![CDATA[") {
+                b"]]>"
+            } else {
+                b">"
+            };
+            let end = match Self::find_tag_end(buf, start + 1, terminator) {
+                Some(end) => end,
+                None => return Scan::Incomplete,
+            };
+            let tag = &buf[start + 1..end + 1 - terminator.len()];
+            i = end + 1;
+
+            match tag.first() {
+                Some(b'?' | b'!') => (),
+                Some(b'/') if depth == 0 => return Scan::Closed,
+                Some(b'/') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Scan::Element(i);
+                    }
+                },
+                _ if tag.ends_with(b"/") => if depth == 0 {
+                    return Scan::Element(i);
+                },
+                _ if depth == 0 && stop_at_start => {
+                    let name = tag.split(|b| b.is_ascii_whitespace()).next().unwrap_or_default();
+                    return Scan::Start(name.to_vec(), i);
+                },
+                _ => depth += 1,
+            }
+        }
+    }
+
+    /// Finds the index of the last byte of the given terminator, ignoring
+    /// quoted attribute values in regular tags.
+    fn find_tag_end(buf: &[u8], from: usize, terminator: &[u8]) -> Option<usize> {
+        if terminator != b">" {
+            return buf[from..].windows(terminator.len())
+                .position(|w| w == terminator)
+                .map(|offset| from + offset + terminator.len() - 1);
+        }
+        let mut quote = None;
+        for (offset, &b) in buf[from..].iter().enumerate() {
+            match (quote, b) {
+                (None, b'"' | b'\'') => quote = Some(b),
+                (Some(q), _) if q == b => quote = None,
+                (None, b'>') => return Some(from + offset),
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tokio::io::AsyncWriteExt;
+
+    use crate::util::{Element, Error};
+
+    use super::AsyncElementReader;
+
+    #[tokio::test]
+    async fn test_read_split_elements() {
+        let (mut write, read) = tokio::io::duplex(8);
+        let writer = tokio::spawn(async move {
+            for chunk in ["<?xml version=\"1.0\"?>\n<proto", "col>\n  <joined roomId=\"a\"/>\n  <room roomId=\"a\"><da", "ta class=\"moveRequest\"/></room>", "</protocol>"] {
+                write.write_all(chunk.as_bytes()).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let mut reader = AsyncElementReader::new(read);
+        reader.read_start("protocol").await.unwrap();
+        assert_eq!(reader.read_element().await.unwrap(), Element::from_str(r#"<joined roomId="a"/>"#).unwrap());
+        assert_eq!(reader.read_element().await.unwrap(), Element::from_str(r#"<room roomId="a"><data class="moveRequest"/></room>"#).unwrap());
+        assert!(matches!(reader.read_element().await, Err(Error::Eof)));
+        writer.await.unwrap();
+    }
+}
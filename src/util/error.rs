@@ -4,6 +4,8 @@ use std::num::{ParseIntError, ParseFloatError};
 use quick_xml::Error as XmlError;
 use quick_xml::events::attributes::AttrError;
 
+use crate::game::RuleViolation;
+
 use super::Element;
 
 /// A custom error type that abstracts over
@@ -22,6 +24,7 @@ pub enum Error {
     UnknownVariant(String),
     InvalidState(String),
     ServerError(String),
+    RuleViolation(RuleViolation),
     Eof,
     Custom(String)
 }
@@ -54,6 +57,10 @@ impl From<AttrError> for Error {
     fn from(error: AttrError) -> Self { Self::Xml(error.into()) }
 }
 
+impl From<RuleViolation> for Error {
+    fn from(violation: RuleViolation) -> Self { Self::RuleViolation(violation) }
+}
+
 impl From<String> for Error {
     fn from(error: String) -> Self { Self::Custom(error) }
 }
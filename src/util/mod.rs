@@ -1,9 +1,12 @@
+#[cfg(feature = "async")]
+mod async_element_reader;
 mod error;
 mod result;
 mod macros;
 mod xml;
 
+#[cfg(feature = "async")]
+pub use async_element_reader::*;
 pub use error::*;
 pub use result::*;
-pub use macros::*;
 pub use xml::*;
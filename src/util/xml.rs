@@ -31,7 +31,8 @@ pub struct ElementBuilder<'a> {
 
 impl Element {
     /// Creates a new XML element builder.
-    pub fn new(name: &str) -> ElementBuilder {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &str) -> ElementBuilder<'_> {
         ElementBuilder::new(name)
     }
 
@@ -104,7 +105,7 @@ impl Element {
     fn write_to_impl<W>(&self, writer: &mut Writer<W>) -> Result<()> where W: Write {
         let start = BytesStart::from(self);
         
-        if self.childs.is_empty() {
+        if self.childs.is_empty() && self.content.is_empty() {
             // Write self-closing tag, e.g. <Element/>
             writer.write_event(Event::Empty(start))?;
         } else {
@@ -118,7 +119,7 @@ impl Element {
 
             // Write child elements
             for child in &self.childs {
-                child.write_to(writer)?;
+                child.write_to_impl(writer)?;
             }
             
             // Write closing tag, e.g. </Element>
@@ -175,7 +176,7 @@ impl<'a> ElementBuilder<'a> {
     /// Creates a new XML node builder with the
     /// specified tag name.
     pub fn new(name: &'a str) -> Self {
-        Self { name: name, content: "", attributes: HashMap::new(), childs: Vec::new() }
+        Self { name, content: "", attributes: HashMap::new(), childs: Vec::new() }
     }
     
     /// Sets the tag name of the XML node.
@@ -257,7 +258,6 @@ impl<'a> TryFrom<&BytesStart<'a>> for Element {
             name: str::from_utf8(start.name())?.to_owned(),
             content: String::new(),
             attributes: start.attributes()
-                .into_iter()
                 .map(|res| {
                     let attribute = res?;
                     let key = str::from_utf8(attribute.key)?.to_owned();
@@ -290,6 +290,11 @@ mod tests {
         assert_eq!("<A><B/><C/></A>", format!("{}", Element::new("A").child(Element::new("B")).child(Element::new("C")).build()))
     }
 
+    #[test]
+    fn test_write_content() {
+        assert_eq!("<A>text</A>", format!("{}", Element::new("A").content("text").build()));
+    }
+
     #[test]
     fn test_read() {
         assert_eq!("<Test/>".parse::<Element>().unwrap(), Element::new("Test").build());
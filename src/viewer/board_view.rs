@@ -0,0 +1,138 @@
+use std::io::{self, Write};
+use crossterm::queue;
+use crossterm::style::{Attribute, Color, ContentStyle, Print, PrintStyledContent};
+use crate::game::{Board, Doubled, Field, Move, State, Team, Vec2, BOARD_SIZE};
+
+/// The width of a rendered field (including the gap to the next field).
+const FIELD_WIDTH: usize = 4;
+
+/// The color the penguins of the given team are rendered in.
+pub fn team_color(team: Team) -> Color {
+    match team {
+        Team::One => Color::Red,
+        Team::Two => Color::Blue,
+    }
+}
+
+/// Renders the hex board of a state to the terminal, shifting every
+/// other row by half a field and highlighting the last move, a
+/// selected penguin and the fields it can move to.
+#[derive(Debug, Clone, Copy)]
+pub struct BoardView<'a> {
+    state: &'a State,
+    last_move: Option<Move>,
+    selected: Option<Vec2<Doubled>>,
+}
+
+impl<'a> BoardView<'a> {
+    /// Creates a view of the given state.
+    pub fn new(state: &'a State) -> Self {
+        Self { state, last_move: None, selected: None }
+    }
+
+    /// Sets the move to highlight as the last one.
+    pub fn with_last_move(self, last_move: Option<Move>) -> Self {
+        Self { last_move, ..self }
+    }
+
+    /// Sets the penguin whose possible moves should be highlighted.
+    pub fn with_selected(self, selected: Option<Vec2<Doubled>>) -> Self {
+        Self { selected, ..self }
+    }
+
+    /// Writes the board to the given terminal, ending each line with a carriage
+    /// return and a line feed (since raw mode does not translate line feeds).
+    pub fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        let board = self.state.board();
+        let targets: Vec<_> = self.selected
+            .map(|s| board.possible_moves_from(s).map(|m| m.to()).collect())
+            .unwrap_or_default();
+
+        queue!(out, Print("   "))?;
+        for x in 0..BOARD_SIZE {
+            queue!(out, Print(format!(" {:<width$}", (b'A' + x as u8) as char, width = FIELD_WIDTH - 1)))?;
+        }
+        queue!(out, Print("\r\n"))?;
+
+        for y in 0..BOARD_SIZE {
+            let indent = if y % 2 == 1 { FIELD_WIDTH / 2 } else { 0 };
+            queue!(out, Print(format!("{:>2} {:indent$}", y + 1, "", indent = indent)))?;
+            for x in 0..BOARD_SIZE {
+                let coords = Vec2::<Doubled>::new(2 * x as i32 + y as i32 % 2, y as i32);
+                let (text, style) = self.field(board, coords, &targets);
+                queue!(out, PrintStyledContent(style.apply(text)), Print(" "))?;
+            }
+            queue!(out, Print("\r\n"))?;
+        }
+        Ok(())
+    }
+
+    /// The text and style of the given field.
+    fn field(&self, board: &Board, coords: Vec2<Doubled>, targets: &[Vec2<Doubled>]) -> (String, ContentStyle) {
+        let field = board.get(coords).unwrap_or(Field::EMPTY);
+        let mut style = ContentStyle::new();
+        let text = match field.penguin() {
+            Some(team) => {
+                style.foreground_color = Some(team_color(team));
+                style.attributes.set(Attribute::Bold);
+                format!("({})", team.letter())
+            },
+            None if field.fish() > 0 => format!(" {} ", field.fish()),
+            None => {
+                style.foreground_color = Some(Color::DarkGrey);
+                " · ".to_owned()
+            },
+        };
+        style.background_color = if self.selected == Some(coords) {
+            Some(Color::White)
+        } else if targets.contains(&coords) {
+            Some(Color::DarkGreen)
+        } else if self.last_move.map(|m| m.to()) == Some(coords) {
+            Some(Color::DarkYellow)
+        } else if self.last_move.and_then(|m| m.from()) == Some(coords) {
+            Some(Color::DarkGrey)
+        } else {
+            None
+        };
+        (text, style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, Field, State, Team, Vec2, Doubled, BOARD_FIELDS};
+
+    use super::BoardView;
+
+    /// Removes the ANSI escape sequences from the given output.
+    fn strip_ansi(output: &[u8]) -> String {
+        let mut text = String::new();
+        let output = String::from_utf8_lossy(output);
+        let mut chars = output.chars();
+        while let Some(c) = chars.next() {
+            if c == '\u{1b}' {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_render() {
+        let mut board = Board::new([Field::with_fish(1); BOARD_FIELDS]);
+        board.set(Vec2::<Doubled>::new(1, 1), Field::with_penguin(Team::One));
+        board.set(Vec2::<Doubled>::new(2, 0), Field::EMPTY);
+        let state = State::new(board, 0, [0, 0], None, Team::One);
+
+        let mut output = Vec::new();
+        BoardView::new(&state).with_selected(Some(Vec2::new(1, 1))).render(&mut output).unwrap();
+        let text = strip_ansi(&output);
+        let lines: Vec<_> = text.split("\r\n").collect();
+        assert_eq!(lines[0].trim(), "A   B   C   D   E   F   G   H");
+        assert_eq!(lines[1], " 1  1   ·   1   1   1   1   1   1  ");
+        assert_eq!(lines[2], " 2   (R)  1   1   1   1   1   1   1  ");
+        assert_eq!(lines.len(), 10);
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::{self, Write};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::queue;
+use crossterm::cursor::MoveTo;
+use crossterm::style::{Attribute, Print, PrintStyledContent, Stylize};
+use crossterm::terminal::{Clear, ClearType};
+use crate::game::{Doubled, GameHistory, Move, State, Team, Vec2};
+use crate::protocol::GameResult;
+use crate::replay::{GameReplay, ReplayCursor};
+
+use super::{BoardView, team_color};
+
+/// The interactive state of the terminal viewer, which shows one
+/// state of a replayed or live game at a time and lets the user step
+/// through the states and inspect the moves of the current team's
+/// penguins using the keyboard.
+#[derive(Debug, Clone)]
+pub struct GameViewer {
+    title: String,
+    history: GameHistory,
+    result: Option<GameResult>,
+    status: Option<String>,
+    index: usize,
+    /// Whether the viewer jumps to new states as they arrive.
+    follow: bool,
+    /// The index of the selected penguin among the current team's movable ones.
+    selected: Option<usize>,
+}
+
+impl GameViewer {
+    /// Creates a viewer for a live game, following
+    /// the states as they are pushed.
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            history: GameHistory::new(),
+            result: None,
+            status: None,
+            index: 0,
+            follow: true,
+            selected: None,
+        }
+    }
+
+    /// Creates a viewer for the given replay, starting at its initial state.
+    pub fn from_replay(title: &str, replay: &GameReplay) -> Self {
+        Self {
+            history: replay.history().clone(),
+            result: replay.result().cloned(),
+            follow: false,
+            ..Self::new(title)
+        }
+    }
+
+    /// Records a new state of the game.
+    pub fn push_state(&mut self, state: State) {
+        self.history.push(state);
+        if self.follow {
+            self.step(|c| c.seek_end());
+        }
+    }
+
+    /// Records the game's result.
+    pub fn set_result(&mut self, result: GameResult) {
+        self.result = Some(result);
+    }
+
+    /// Sets a message to display, e.g. a connection error.
+    pub fn set_status(&mut self, status: &str) {
+        self.status = Some(status.to_owned());
+    }
+
+    /// Fetches the index of the displayed state.
+    pub fn index(&self) -> usize { self.index }
+
+    /// Fetches the displayed state.
+    pub fn state(&self) -> Option<&State> { self.cursor().state() }
+
+    /// Fetches the move leading to the displayed state.
+    pub fn last_move(&self) -> Option<Move> { self.cursor().last_move() }
+
+    /// Creates a cursor at the displayed state.
+    fn cursor(&self) -> ReplayCursor<'_> {
+        let mut cursor = ReplayCursor::new(&self.history);
+        cursor.seek(self.index);
+        cursor
+    }
+
+    /// Fetches the selected penguin's position.
+    pub fn selected_penguin(&self) -> Option<Vec2<Doubled>> {
+        self.selected.and_then(|i| self.movable_penguins().get(i).copied())
+    }
+
+    /// The positions of the current team's penguins that can move.
+    fn movable_penguins(&self) -> Vec<Vec2<Doubled>> {
+        match self.state() {
+            Some(state) if state.penguins_placed() => state.current_pieces()
+                .map(|(c, _)| c)
+                .filter(|&c| state.board().can_move_from(c))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Moves the displayed state using the given cursor operation,
+    /// following new states if it ends at the most recent one.
+    fn step(&mut self, f: impl FnOnce(&mut ReplayCursor<'_>)) {
+        let mut cursor = self.cursor();
+        f(&mut cursor);
+        let (index, follow) = (cursor.index(), cursor.is_at_end());
+        if index != self.index {
+            self.selected = None;
+        }
+        self.index = index;
+        self.follow = follow;
+    }
+
+    /// Cycles the selection through the movable penguins in the given direction.
+    fn cycle_selection(&mut self, forward: bool) {
+        let count = self.movable_penguins().len();
+        self.selected = match (self.selected, count) {
+            (_, 0) => None,
+            (None, _) => Some(if forward { 0 } else { count - 1 }),
+            (Some(i), _) if forward => Some((i + 1) % count),
+            (Some(i), _) => Some((i + count - 1) % count),
+        };
+    }
+
+    /// Handles the given key press, returning whether the viewer should keep running.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Right => self.step(|c| { c.step_forward(); }),
+            KeyCode::Left => self.step(|c| { c.step_backward(); }),
+            KeyCode::Home => self.step(|c| c.seek_start()),
+            KeyCode::End => self.step(|c| c.seek_end()),
+            KeyCode::Down | KeyCode::Tab => self.cycle_selection(true),
+            KeyCode::Up | KeyCode::BackTab => self.cycle_selection(false),
+            KeyCode::Esc => self.selected = None,
+            _ => (),
+        }
+        true
+    }
+
+    /// Clears the terminal and draws the displayed state.
+    pub fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        let total = self.history.states().len();
+        let live = if self.follow && self.result.is_none() { " (live)" } else { "" };
+        queue!(out, PrintStyledContent(self.title.as_str().bold()), Print(format!(" · state {}/{}{}\r\n", self.index + 1, total, live)))?;
+
+        let Some(state) = self.state() else {
+            queue!(out, Print("\r\nWaiting for the game to start...\r\n"))?;
+            return self.render_footer(out);
+        };
+
+        queue!(out, Print(format!("Turn {} · ", state.turn())))?;
+        for team in [Team::One, Team::Two] {
+            queue!(out, PrintStyledContent(format!("{}: {} fish", team.letter(), state.fish(team)).with(team_color(team))), Print("  "))?;
+        }
+        if state.is_over() {
+            queue!(out, Print("· game over\r\n\r\n"))?;
+        } else {
+            let team = state.current_team();
+            queue!(out, Print("· "), PrintStyledContent(team.letter().with(team_color(team))), Print(" to move\r\n\r\n"))?;
+        }
+
+        let selected = self.selected_penguin();
+        BoardView::new(state).with_last_move(self.last_move()).with_selected(selected).render(out)?;
+        queue!(out, Print("\r\n"))?;
+
+        match self.last_move() {
+            Some(m) => queue!(out, Print(format!("Last move: {}\r\n", m.notation())))?,
+            None => queue!(out, Print("Last move: -\r\n"))?,
+        }
+        if let Some(penguin) = selected {
+            let moves: Vec<_> = state.board().possible_moves_from(penguin).map(|m| m.notation()).collect();
+            queue!(out, Print(format!("Selected: {}\r\n", moves.join(", "))))?;
+        }
+        if let Some(result) = self.result.as_ref().filter(|_| self.index + 1 == total) {
+            let outcome = match result.winner() {
+                Some(winner) => format!("{} wins", winner.team().letter()),
+                None => "Draw".to_owned(),
+            };
+            let causes: Vec<_> = result.scores().iter()
+                .filter(|(_, s)| !s.reason().is_empty())
+                .map(|(p, s)| format!("{}: {} ({})", p.team().letter(), s.cause(), s.reason()))
+                .collect();
+            queue!(out, PrintStyledContent(format!("Result: {}", outcome).attribute(Attribute::Bold)), Print(format!(" {}\r\n", causes.join(", "))))?;
+        }
+        self.render_footer(out)
+    }
+
+    /// Draws the status and the key bindings.
+    fn render_footer(&self, out: &mut impl Write) -> io::Result<()> {
+        if let Some(status) = &self.status {
+            queue!(out, Print(format!("\r\n{}\r\n", status)))?;
+        }
+        queue!(out, PrintStyledContent("\r\n←/→ step · Home/End jump · ↑/↓ select penguin · Esc deselect · q quit\r\n".dark_grey()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+    use crate::{game::State, server::LocalServer};
+
+    use super::GameViewer;
+
+    fn press(viewer: &mut GameViewer, code: KeyCode) -> bool {
+        viewer.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_stepping_and_selection() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let mut state = *LocalServer::random(&mut rng).state();
+        let mut states = vec![state];
+        while let Some(&m) = state.possible_moves().choose(&mut rng) {
+            state.perform(m);
+            states.push(state);
+        }
+
+        // A live viewer follows new states until stepping back
+        let mut viewer = GameViewer::new("test");
+        assert_eq!(viewer.state(), None);
+        for &s in &states[..10] {
+            viewer.push_state(s);
+        }
+        assert_eq!(viewer.state(), Some(&states[9]));
+        assert!(press(&mut viewer, KeyCode::Left));
+        viewer.push_state(states[10]);
+        assert_eq!(viewer.state(), Some(&states[8]));
+        assert_eq!(viewer.last_move(), states[8].last_move());
+        press(&mut viewer, KeyCode::End);
+        viewer.push_state(states[11]);
+        assert_eq!(viewer.state(), Some(&states[11]));
+        press(&mut viewer, KeyCode::Home);
+        assert_eq!(viewer.index(), 0);
+        assert_eq!(viewer.last_move(), None);
+
+        // Penguins can only be selected once placed
+        press(&mut viewer, KeyCode::Down);
+        assert_eq!(viewer.selected_penguin(), None);
+        press(&mut viewer, KeyCode::End);
+        press(&mut viewer, KeyCode::Down);
+        let selected = viewer.selected_penguin().unwrap();
+        let current: &State = viewer.state().unwrap();
+        assert_eq!(current.board().get(selected).unwrap().penguin(), Some(current.current_team()));
+        press(&mut viewer, KeyCode::Down);
+        press(&mut viewer, KeyCode::Up);
+        assert_eq!(viewer.selected_penguin(), Some(selected));
+        press(&mut viewer, KeyCode::Esc);
+        assert_eq!(viewer.selected_penguin(), None);
+        press(&mut viewer, KeyCode::Down);
+        press(&mut viewer, KeyCode::Left);
+        assert_eq!(viewer.selected_penguin(), None);
+
+        let mut output = Vec::new();
+        viewer.render(&mut output).unwrap();
+        assert!(String::from_utf8_lossy(&output).contains("state 11/12"));
+        assert!(!press(&mut viewer, KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,5 @@
+mod board_view;
+mod game_viewer;
+
+pub use board_view::*;
+pub use game_viewer::*;